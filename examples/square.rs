@@ -0,0 +1,90 @@
+//! A single spinning square in a window. Run with `cargo run --example square`, V toggles vsync.
+
+use reverie::app::run;
+use reverie::{uv, winit, App, AppConfig, Context, GameObject, ReverieError, Schedule, Transform2DComponent, Vertex, World};
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
+
+/// Turns the entity by this many radians per second.
+struct Spin(f32);
+
+fn spin(world: &mut World, dt: f32) {
+    for (_, spin, transform) in world.query2_mut::<Spin, Transform2DComponent>() {
+        transform.rotation += spin.0 * dt;
+    }
+}
+
+struct Square {
+    systems: Schedule,
+}
+
+impl App for Square {
+    fn init(&mut self, ctx: &mut Context) -> Result<(), ReverieError> {
+        let vertices: [Vertex; 4] = [
+            Vertex {
+                pos: uv::Vec2::new(-0.5, -0.5),
+                color: uv::Vec3::new(1.0, 0.0, 0.0),
+                tex_coord: uv::Vec2::new(0.0, 1.0),
+            },
+            Vertex {
+                pos: uv::Vec2::new(0.5, -0.5),
+                color: uv::Vec3::new(0.0, 1.0, 0.0),
+                tex_coord: uv::Vec2::new(1.0, 1.0),
+            },
+            Vertex {
+                pos: uv::Vec2::new(0.5, 0.5),
+                color: uv::Vec3::new(0.0, 0.0, 1.0),
+                tex_coord: uv::Vec2::new(1.0, 0.0),
+            },
+            Vertex {
+                pos: uv::Vec2::new(-0.5, 0.5),
+                color: uv::Vec3::new(1.0, 1.0, 1.0),
+                tex_coord: uv::Vec2::new(0.0, 0.0),
+            },
+        ];
+
+        let indices: [u32; 6] = [
+            0, 1, 2,
+            2, 3, 0
+        ];
+
+        let mesh = ctx.renderer.create_static_mesh(&vertices, &indices)?;
+
+        let mut square = GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0));
+        square.transform2d.translation.x = 0.2;
+
+        let square = ctx.renderer.spawn(square);
+        ctx.renderer.world.insert(square, Spin(0.5));
+        Ok(())
+    }
+
+    fn fixed_update(&mut self, ctx: &mut Context, dt: f32) {
+        self.systems.run(&mut ctx.renderer.world, dt);
+    }
+
+    fn on_event(&mut self, ctx: &mut Context, event: &WindowEvent) {
+        if let WindowEvent::KeyboardInput {
+            input: KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::V),
+                ..
+            },
+            ..
+        } = event {
+            let vsync = !ctx.renderer.swapchain_config.is_vsync();
+            println!("[Reverie][info] Vsync {}", if vsync { "on" } else { "off" });
+            ctx.renderer.set_vsync(vsync);
+        }
+    }
+}
+
+fn main() -> Result<(), ReverieError> {
+    let config = AppConfig {
+        title: "Reverie",
+        // Edit the files in shaders/ while the demo runs to see the changes without rebuilding
+        shader_hot_reload: cfg!(debug_assertions),
+        show_fps: true,
+        ..AppConfig::default()
+    };
+
+    run(config, Square { systems: Schedule::new().with_system(spin) })
+}
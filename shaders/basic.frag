@@ -5,6 +5,7 @@ layout (location = 0) out vec4 color;
 layout(push_constant) uniform Push {
     mat2 transform;
     vec2 offset;
+    float depth;
     vec3 color;
 } push;
 
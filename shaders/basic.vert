@@ -3,10 +3,17 @@
 layout(location = 0) in vec2 in_position;
 layout(location = 1) in vec3 in_color;
 
+layout(set = 0, binding = 0) uniform Globals {
+    mat4 view_projection;
+    mat4 view;
+    mat4 projection;
+    vec3 camera_position;
+} globals;
 
 layout(push_constant) uniform Push {
     mat2 transform;
     vec2 offset;
+    float depth;
     vec3 color;
 } push;
 
@@ -15,7 +22,12 @@ layout(push_constant) uniform Push {
 // };
 
 void main() {
-    gl_Position = vec4(push.transform * in_position + push.offset.xy, 0.0, 1.0);
+    vec2 world_position = push.transform * in_position + push.offset;
+    gl_Position = globals.view_projection * vec4(world_position, 0.0, 1.0);
+    // 2D layering ignores the camera depth range, scale by w so it survives the perspective divide
+    gl_Position.z = push.depth * gl_Position.w;
+    // Only read by point topologies
+    gl_PointSize = 1.0;
 
     //out_color = in_color;
-}
\ No newline at end of file
+}
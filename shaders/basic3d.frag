@@ -0,0 +1,20 @@
+#version 450
+
+layout(location = 0) in vec3 in_normal;
+layout(location = 1) in vec2 in_tex_coord;
+
+layout (location = 0) out vec4 color;
+
+layout(push_constant) uniform Push {
+    mat4 model;
+    mat3 normal_matrix;
+    vec3 color;
+} push;
+
+const vec3 LIGHT_DIRECTION = normalize(vec3(0.5, -1.0, 0.3));
+const float AMBIENT = 0.2;
+
+void main() {
+    float diffuse = max(dot(normalize(in_normal), -LIGHT_DIRECTION), 0.0);
+    color = vec4(push.color * (AMBIENT + diffuse), 1.0);
+}
@@ -0,0 +1,30 @@
+#version 450
+
+layout(location = 0) in vec3 in_position;
+layout(location = 1) in vec3 in_normal;
+layout(location = 2) in vec2 in_tex_coord;
+
+layout(location = 0) out vec3 out_normal;
+layout(location = 1) out vec2 out_tex_coord;
+
+layout(set = 0, binding = 0) uniform Globals {
+    mat4 view_projection;
+    mat4 view;
+    mat4 projection;
+    vec3 camera_position;
+} globals;
+
+layout(push_constant) uniform Push {
+    mat4 model;
+    mat3 normal_matrix;
+    vec3 color;
+} push;
+
+void main() {
+    gl_Position = globals.view_projection * push.model * vec4(in_position, 1.0);
+    // Only read by point topologies
+    gl_PointSize = 1.0;
+
+    out_normal = normalize(push.normal_matrix * in_normal);
+    out_tex_coord = in_tex_coord;
+}
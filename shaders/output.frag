@@ -0,0 +1,69 @@
+#version 450
+
+layout(location = 0) in vec2 in_tex_coord;
+
+layout(location = 0) out vec4 color;
+
+// The scene in linear Rec. 709, 1.0 being paper white
+layout(set = 0, binding = 0) uniform sampler2D scene;
+
+layout(push_constant) uniform Push {
+    uint encoding;
+    float paper_white_nits;
+    float max_nits;
+} push;
+
+// Values of `OutputEncoding`
+const uint ENCODING_SRGB = 1;
+const uint ENCODING_SCRGB = 2;
+const uint ENCODING_HDR10 = 3;
+
+vec3 linear_to_srgb(vec3 linear) {
+    linear = clamp(linear, 0.0, 1.0);
+    return mix(linear * 12.92, 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, linear));
+}
+
+// Leaves everything up to paper white alone and rolls brighter values off towards `peak`
+vec3 tonemap(vec3 linear, float peak) {
+    float knee = min(1.0, peak);
+    vec3 over = max(linear - knee, 0.0);
+    float range = max(peak - knee, 1e-4);
+    return min(linear, knee) + range * (1.0 - exp(-over / range));
+}
+
+vec3 rec709_to_rec2020(vec3 linear) {
+    const mat3 conversion = mat3(
+        0.6274, 0.0691, 0.0164,
+        0.3293, 0.9195, 0.0880,
+        0.0433, 0.0114, 0.8956
+    );
+    return conversion * linear;
+}
+
+// SMPTE ST 2084 for absolute luminance in nits
+vec3 pq_encode(vec3 nits) {
+    const float m1 = 0.1593017578125;
+    const float m2 = 78.84375;
+    const float c1 = 0.8359375;
+    const float c2 = 18.8515625;
+    const float c3 = 18.6875;
+    vec3 y = pow(clamp(nits / 10000.0, 0.0, 1.0), vec3(m1));
+    return pow((c1 + c2 * y) / (1.0 + c3 * y), vec3(m2));
+}
+
+void main() {
+    vec4 linear = texture(scene, in_tex_coord);
+    float peak = push.max_nits / push.paper_white_nits;
+
+    vec3 encoded;
+    if (push.encoding == ENCODING_SCRGB) {
+        // scRGB is linear Rec. 709 with 1.0 at 80 nits
+        encoded = tonemap(max(linear.rgb, 0.0), peak) * (push.paper_white_nits / 80.0);
+    } else if (push.encoding == ENCODING_HDR10) {
+        encoded = pq_encode(rec709_to_rec2020(tonemap(max(linear.rgb, 0.0), peak)) * push.paper_white_nits);
+    } else {
+        encoded = linear_to_srgb(linear.rgb);
+    }
+
+    color = vec4(encoded, linear.a);
+}
@@ -0,0 +1,19 @@
+#version 450
+
+layout(location = 0) in vec2 in_tex_coord;
+
+layout (location = 0) out vec4 color;
+
+layout(set = 1, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform Push {
+    mat2 transform;
+    vec2 offset;
+    float depth;
+    vec3 color;
+} push;
+
+void main() {
+    // The object color tints the texture, white leaves it unchanged
+    color = texture(tex, in_tex_coord) * vec4(push.color, 1.0);
+}
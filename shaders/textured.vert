@@ -0,0 +1,31 @@
+#version 450
+
+layout(location = 0) in vec2 in_position;
+layout(location = 1) in vec3 in_color;
+layout(location = 2) in vec2 in_tex_coord;
+
+layout(location = 0) out vec2 out_tex_coord;
+
+layout(set = 0, binding = 0) uniform Globals {
+    mat4 view_projection;
+    mat4 view;
+    mat4 projection;
+    vec3 camera_position;
+} globals;
+
+layout(push_constant) uniform Push {
+    mat2 transform;
+    vec2 offset;
+    float depth;
+    vec3 color;
+} push;
+
+void main() {
+    vec2 world_position = push.transform * in_position + push.offset;
+    gl_Position = globals.view_projection * vec4(world_position, 0.0, 1.0);
+    gl_Position.z = push.depth * gl_Position.w;
+    // Only read by point topologies
+    gl_PointSize = 1.0;
+
+    out_tex_coord = in_tex_coord;
+}
@@ -0,0 +1,173 @@
+use std::time::{Duration, Instant};
+
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::ControlFlow;
+
+use crate::vulkan::renderer::VulkanRenderer;
+use crate::vulkan::swapchain::SwapchainConfig;
+use crate::vulkan::window::VulkanWindow;
+use crate::vulkan::error::ReverieError;
+use crate::time::FixedTimestep;
+
+/// A game, driven by `run`. Every hook has an empty default, implement the ones that are needed.
+///
+/// Each frame, `fixed_update` runs zero or more times to catch the simulation up with the clock,
+/// then `update` and `render` run once before the frame is drawn. Game objects are drawn
+/// interpolated between their last two fixed steps, so movement stays smooth at any frame rate.
+pub trait App: 'static {
+    /// Called once after the window and renderer exist, to load meshes and textures and spawn objects.
+    fn init(&mut self, _ctx: &mut Context) -> Result<(), ReverieError> {
+        Ok(())
+    }
+
+    /// Advances the simulation by `dt`, which is always `AppConfig::fixed_timestep` in seconds.
+    /// Put physics and gameplay here, it steps the same way on every machine.
+    fn fixed_update(&mut self, _ctx: &mut Context, _dt: f32) {}
+
+    /// Called once per frame with the real time since the previous frame, in seconds.
+    fn update(&mut self, _ctx: &mut Context, _dt: f32) {}
+
+    /// Called right before the frame is drawn, to move the game state into the renderer.
+    fn render(&mut self, _ctx: &mut Context) {}
+
+    /// Called for every window event, after the runner has handled resizes.
+    fn on_event(&mut self, _ctx: &mut Context, _event: &WindowEvent) {}
+}
+
+/// How `run` sets up the window and the loop.
+#[derive(Clone, Debug)]
+pub struct AppConfig {
+    pub title: &'static str,
+    /// Initial size of the window in logical pixels.
+    pub width: u32,
+    pub height: u32,
+    pub swapchain: SwapchainConfig,
+    /// Time simulated by each `App::fixed_update`.
+    pub fixed_timestep: Duration,
+    /// Fixed steps per frame at most. Frames slower than that slow the simulation down instead of
+    /// making every following frame slower still.
+    pub max_fixed_steps: u32,
+    /// Rebuilds pipelines when their shader files change, see `VulkanRenderer::enable_shader_hot_reload`.
+    pub shader_hot_reload: bool,
+    /// Appends the frame rate and frame time to the window title.
+    pub show_fps: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            title: "Reverie",
+            width: 800,
+            height: 600,
+            swapchain: SwapchainConfig::default(),
+            fixed_timestep: Duration::from_nanos(1_000_000_000 / 60),
+            max_fixed_steps: 8,
+            shader_hot_reload: false,
+            show_fps: false
+        }
+    }
+}
+
+/// What the hooks of an `App` get to work with.
+pub struct Context {
+    // Declared before the window, the surface has to go first
+    pub renderer: VulkanRenderer,
+    pub window: VulkanWindow,
+    delta_time: f32,
+    exit_requested: bool,
+}
+
+impl Context {
+    /// Seconds between the previous frame and this one.
+    pub fn delta_time(&self) -> f32 {
+        self.delta_time
+    }
+
+    /// Closes the window and ends the loop after the current frame.
+    pub fn exit(&mut self) {
+        self.exit_requested = true;
+    }
+}
+
+/// Creates the window and the renderer, calls `App::init` and runs the main loop until the window
+/// is closed or `Context::exit` is called. Only returns if setting up fails, errors while running
+/// are logged and end the process with a non-zero exit code.
+pub fn run<A: App>(config: AppConfig, mut app: A) -> Result<(), ReverieError> {
+    let (event_loop, window) = VulkanWindow::create_window(config.title, config.width, config.height)?;
+
+    let mut renderer = VulkanRenderer::with_swapchain_config(&window, config.swapchain.clone())?;
+    if config.shader_hot_reload {
+        renderer.enable_shader_hot_reload()?;
+    }
+
+    let mut ctx = Context {
+        renderer,
+        window,
+        delta_time: 0.0,
+        exit_requested: false
+    };
+
+    app.init(&mut ctx)?;
+
+    let mut last_frame = Instant::now();
+    let mut timestep = FixedTimestep::new(config.fixed_timestep, config.max_fixed_steps);
+
+    event_loop.run(move |event, _, controlflow| {
+        match event {
+            Event::WindowEvent { event, .. } => {
+                match &event {
+                    WindowEvent::CloseRequested => ctx.exit(),
+                    WindowEvent::Resized(size) => ctx.renderer.resize(size.width, size.height),
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        ctx.renderer.resize(new_inner_size.width, new_inner_size.height);
+                    },
+                    _ => {}
+                }
+                app.on_event(&mut ctx, &event);
+            },
+            Event::MainEventsCleared => {
+                // Sleep until the window is restored instead of spinning on empty frames, and
+                // don't count the time spent minimized as a frame
+                if ctx.renderer.is_minimized() {
+                    *controlflow = ControlFlow::Wait;
+                    last_frame = Instant::now();
+                } else {
+                    *controlflow = ControlFlow::Poll;
+                    ctx.window.window.request_redraw();
+                }
+            },
+            Event::RedrawRequested(_) => {
+                let now = Instant::now();
+                let frame_time = now.duration_since(last_frame);
+                last_frame = now;
+                let delta_time = frame_time.as_secs_f32();
+                ctx.delta_time = delta_time;
+
+                for _ in 0..timestep.advance(frame_time) {
+                    ctx.renderer.save_transforms();
+                    app.fixed_update(&mut ctx, timestep.dt());
+                }
+                ctx.renderer.interpolation_alpha = timestep.alpha();
+
+                app.update(&mut ctx, delta_time);
+                app.render(&mut ctx);
+
+                if config.show_fps {
+                    ctx.window.window.set_title(&format!("{} - FPS: {:.0} ({:.3}ms)",
+                        config.title, 1.0 / delta_time.max(f32::EPSILON), delta_time * 1000.0));
+                }
+
+                if let Err(err) = ctx.renderer.draw_frame() {
+                    println!("[Reverie][error] {}", err);
+                    *controlflow = ControlFlow::ExitWithCode(1);
+                    return;
+                }
+            },
+            _ => {}
+        }
+
+        if ctx.exit_requested {
+            *controlflow = ControlFlow::Exit;
+        }
+    });
+}
@@ -0,0 +1,19 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static OBJECT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Refers to an object in a `World`. Ids are unique for the whole process and never reused,
+/// so a handle to a despawned entity can't end up pointing at a new one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entity(usize);
+
+impl Entity {
+    /// A fresh id, higher than every id handed out before.
+    pub fn next() -> Self {
+        Self(OBJECT_COUNTER.fetch_add(1, Ordering::SeqCst))
+    }
+
+    pub fn id(&self) -> usize {
+        self.0
+    }
+}
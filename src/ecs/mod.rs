@@ -0,0 +1,4 @@
+pub mod entity;
+pub mod storage;
+pub mod world;
+pub mod system;
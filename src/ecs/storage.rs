@@ -0,0 +1,114 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use super::entity::Entity;
+
+/// Components of one type, packed densely for iteration, with a sparse map from entity to
+/// index to find them. Lookup, insertion and removal are constant time; removal moves the last
+/// component into the gap, so iteration order is not insertion order.
+///
+/// Entity ids are never reused, so the sparse side is a map rather than an array indexed by id.
+/// Its size follows the number of components, not the highest id ever spawned.
+pub struct SparseSet<T> {
+    sparse: HashMap<Entity, u32>,
+    entities: Vec<Entity>,
+    components: Vec<T>,
+}
+
+impl<T> Default for SparseSet<T> {
+    fn default() -> Self {
+        Self {
+            sparse: HashMap::new(),
+            entities: vec![],
+            components: vec![]
+        }
+    }
+}
+
+impl<T> SparseSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn dense_index(&self, entity: Entity) -> Option<usize> {
+        self.sparse.get(&entity).map(|&index| index as usize)
+    }
+
+    /// Adds the component, or replaces and returns the one the entity already had.
+    pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        if let Some(index) = self.dense_index(entity) {
+            return Some(std::mem::replace(&mut self.components[index], component));
+        }
+
+        self.sparse.insert(entity, self.components.len() as u32);
+        self.entities.push(entity);
+        self.components.push(component);
+        None
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let index = self.sparse.remove(&entity)? as usize;
+
+        self.entities.swap_remove(index);
+        let component = self.components.swap_remove(index);
+        if let Some(moved) = self.entities.get(index) {
+            self.sparse.insert(*moved, index as u32);
+        }
+        Some(component)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.dense_index(entity).map(|index| &self.components[index])
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.dense_index(entity).map(|index| &mut self.components[index])
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.dense_index(entity).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// The entities that have the component, in the order of `iter`.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entities.iter().copied().zip(self.components.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.entities.iter().copied().zip(self.components.iter_mut())
+    }
+}
+
+/// Lets the world hold sparse sets of any component type side by side.
+pub(crate) trait ComponentStorage: Any {
+    /// Drops the entity's component, if it has one.
+    fn remove_entity(&mut self, entity: Entity);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> ComponentStorage for SparseSet<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
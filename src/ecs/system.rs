@@ -0,0 +1,48 @@
+use super::world::World;
+
+/// Game logic that runs over the components of a world, like moving everything with a velocity.
+pub trait System {
+    /// `dt` is the time step in seconds the system should advance by.
+    fn run(&mut self, world: &mut World, dt: f32);
+}
+
+impl<F: FnMut(&mut World, f32)> System for F {
+    fn run(&mut self, world: &mut World, dt: f32) {
+        self(world, dt)
+    }
+}
+
+/// Systems run one after another in the order they were added.
+#[derive(Default)]
+pub struct Schedule {
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_system<S: System + 'static>(mut self, system: S) -> Self {
+        self.add_system(system);
+        self
+    }
+
+    pub fn add_system<S: System + 'static>(&mut self, system: S) {
+        self.systems.push(Box::new(system));
+    }
+
+    pub fn run(&mut self, world: &mut World, dt: f32) {
+        for system in &mut self.systems {
+            system.run(world, dt);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+}
@@ -0,0 +1,142 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+
+use super::entity::Entity;
+use super::storage::{ComponentStorage, SparseSet};
+
+/// Entities and their components. Any `'static` type can be a component, each entity has at
+/// most one component of every type.
+///
+/// Components of a type are kept in their own `SparseSet`. Queries walk the set of the first
+/// component type and look the others up by entity.
+#[derive(Default)]
+pub struct World {
+    entities: SparseSet<()>,
+    storages: HashMap<TypeId, Box<dyn ComponentStorage>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an entity without components.
+    pub fn spawn(&mut self) -> Entity {
+        let entity = Entity::next();
+        self.entities.insert(entity, ());
+        entity
+    }
+
+    /// Adds an entity created elsewhere, like the id of a `GameObject`. Does nothing if it is already alive.
+    pub fn spawn_entity(&mut self, entity: Entity) {
+        self.entities.insert(entity, ());
+    }
+
+    /// Removes the entity together with all its components, which are dropped. Components that
+    /// own GPU resources have to be taken out and destroyed first, see `VulkanRenderer::despawn`.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if self.entities.remove(entity).is_none() {
+            return false;
+        }
+
+        for storage in self.storages.values_mut() {
+            storage.remove_entity(entity);
+        }
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.contains(entity)
+    }
+
+    /// Number of living entities.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.entities().iter().copied()
+    }
+
+    /// Adds the component to a living entity, returning the one it replaces.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) -> Option<T> {
+        assert!(self.is_alive(entity), "Cannot add a component to {:?}, it is not alive!", entity);
+        self.storage_or_insert::<T>().insert(entity, component)
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        self.storage_mut::<T>()?.remove(entity)
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(entity)
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.storage_mut::<T>()?.get_mut(entity)
+    }
+
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool {
+        self.storage::<T>().is_some_and(|storage| storage.contains(entity))
+    }
+
+    /// All components of type `T`, `None` if none was ever added.
+    pub fn storage<T: 'static>(&self) -> Option<&SparseSet<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .map(|storage| storage.as_any().downcast_ref().unwrap())
+    }
+
+    pub fn storage_mut<T: 'static>(&mut self) -> Option<&mut SparseSet<T>> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())
+            .map(|storage| storage.as_any_mut().downcast_mut().unwrap())
+    }
+
+    fn storage_or_insert<T: 'static>(&mut self) -> &mut SparseSet<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(SparseSet::<T>::new()))
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap()
+    }
+
+    /// Every entity with a `T`, in no particular order.
+    pub fn query<T: 'static>(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.storage::<T>().into_iter().flat_map(|storage| storage.iter())
+    }
+
+    pub fn query_mut<T: 'static>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.storage_mut::<T>().into_iter().flat_map(|storage| storage.iter_mut())
+    }
+
+    /// Every entity with both an `A` and a `B`.
+    pub fn query2<A: 'static, B: 'static>(&self) -> impl Iterator<Item = (Entity, &A, &B)> {
+        let b = self.storage::<B>();
+        self.query::<A>().filter_map(move |(entity, a)| Some((entity, a, b?.get(entity)?)))
+    }
+
+    /// Like `query2`, with both components mutable. `A` and `B` have to be different types.
+    pub fn query2_mut<A: 'static, B: 'static>(&mut self) -> impl Iterator<Item = (Entity, &mut A, &mut B)> {
+        assert_ne!(TypeId::of::<A>(), TypeId::of::<B>(), "Cannot borrow a component type mutably twice!");
+
+        let [a, b] = self.storages.get_disjoint_mut([&TypeId::of::<A>(), &TypeId::of::<B>()]);
+        let a = a.map(|storage| storage.as_any_mut().downcast_mut::<SparseSet<A>>().unwrap());
+        let b = b.map(|storage| storage.as_any_mut().downcast_mut::<SparseSet<B>>().unwrap());
+
+        let pairs: Box<dyn Iterator<Item = (Entity, &mut A, &mut B)>> = match (a, b) {
+            (Some(a), Some(b)) => {
+                // Each `B` is moved out of the map when its entity comes up in `a`
+                let mut b: HashMap<Entity, &mut B> = b.iter_mut().collect();
+                Box::new(a.iter_mut().filter_map(move |(entity, a)| Some((entity, a, b.remove(&entity)?))))
+            },
+            _ => Box::new(std::iter::empty())
+        };
+        pairs
+    }
+}
@@ -0,0 +1,35 @@
+//! Reverie, a small Vulkan engine.
+//!
+//! The types most games need are re-exported here. Implement `app::App` for the game and hand it
+//! to `app::run`, which creates the window and the `VulkanRenderer` and drives the main loop.
+//! Game state lives in the `ecs::World` of the renderer, `GameObject`s spawned into it with
+//! `VulkanRenderer::spawn` are drawn. Everything else lives in the `vulkan` and `utils` modules.
+//! See `examples/` for complete programs.
+
+pub mod vulkan;
+pub mod utils;
+pub mod app;
+pub mod time;
+pub mod ecs;
+
+pub use app::{App, AppConfig, Context};
+pub use ecs::entity::Entity;
+pub use ecs::world::World;
+pub use ecs::system::{Schedule, System};
+pub use vulkan::renderer::VulkanRenderer;
+pub use vulkan::window::VulkanWindow;
+pub use vulkan::swapchain::SwapchainConfig;
+pub use vulkan::output::HdrSettings;
+pub use vulkan::mesh::Mesh;
+pub use vulkan::vertex::{Vertex, Vertex3D};
+pub use vulkan::game_object::{Color, GameObject, Previous, Transform2DComponent, Transform3DComponent};
+pub use vulkan::scene::{Children, GlobalTransform2D, GlobalTransform3D, Parent};
+pub use vulkan::camera::{Camera, FirstPersonController, OrbitController, Projection};
+pub use vulkan::pipeline::{BlendMode, PipelineBuilder, PipelineHandle};
+pub use vulkan::texture::{TextureHandle, TextureOptions};
+pub use vulkan::error::ReverieError;
+
+// The versions the engine is built against, so games use the same types
+pub use ash::{self, vk};
+pub use uv;
+pub use winit;
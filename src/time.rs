@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+/// Turns variable frame times into a whole number of fixed simulation steps.
+///
+/// Frame time is collected in an accumulator and spent in steps of exactly `step`, so the
+/// simulation sees the same `dt` on every machine and at any frame rate. The time left over is
+/// reported by `alpha` for interpolating between the last two simulated states.
+///
+/// The accumulator counts whole nanoseconds, it doesn't drift the way summing floats would.
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+}
+
+impl FixedTimestep {
+    /// At most `max_steps` are taken per frame. When a frame takes longer than that, the rest
+    /// is dropped and the simulation slows down instead of falling further and further behind.
+    pub fn new(step: Duration, max_steps: u32) -> Self {
+        assert!(!step.is_zero(), "The fixed timestep has to be longer than zero!");
+        assert!(max_steps > 0, "At least one step per frame has to be allowed!");
+
+        Self {
+            step,
+            max_steps,
+            accumulator: Duration::ZERO
+        }
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// The step in seconds, as passed to `App::fixed_update`.
+    pub fn dt(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    /// Adds the time of a frame and returns how many steps to simulate for it.
+    pub fn advance(&mut self, frame_time: Duration) -> u32 {
+        self.accumulator += frame_time;
+
+        let steps = (self.accumulator.as_nanos() / self.step.as_nanos()) as u64;
+        if steps > self.max_steps as u64 {
+            // Keep the fraction of a step, so interpolation carries on smoothly
+            self.accumulator = Duration::from_nanos((self.accumulator.as_nanos() % self.step.as_nanos()) as u64);
+            return self.max_steps;
+        }
+
+        self.accumulator -= self.step * steps as u32;
+        steps as u32
+    }
+
+    /// How far the clock is past the last step, as a fraction of a step in [0, 1).
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator.as_secs_f64() / self.step.as_secs_f64()) as f32
+    }
+
+    /// Forgets the accumulated time, after a pause for example.
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+    }
+}
@@ -1,2 +1,3 @@
 #[repr(align(16))]
+#[derive(Clone, Copy, Debug)]
 pub struct Align16<T>(pub T);
\ No newline at end of file
@@ -0,0 +1,29 @@
+//! Colors in the API are sRGB, the way they are picked in image editors and color pickers.
+//! Shaders work with linear values, which blend and light correctly, and the sRGB render
+//! targets encode the result again when it is written.
+
+/// Decodes a single sRGB channel in [0, 1].
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a single linear channel in [0, 1].
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn srgb_to_linear_rgb(color: uv::Vec3) -> uv::Vec3 {
+    uv::Vec3::new(srgb_to_linear(color.x), srgb_to_linear(color.y), srgb_to_linear(color.z))
+}
+
+pub fn linear_to_srgb_rgb(color: uv::Vec3) -> uv::Vec3 {
+    uv::Vec3::new(linear_to_srgb(color.x), linear_to_srgb(color.y), linear_to_srgb(color.z))
+}
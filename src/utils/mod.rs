@@ -1,4 +1,5 @@
 pub mod align;
+pub mod color;
 
 pub unsafe fn any_as_u8_slice<T: Sized>(p: &T) -> &[u8] {
     std::slice::from_raw_parts(
@@ -0,0 +1,73 @@
+use ash::vk;
+use gpu_allocator::vulkan::*;
+use gpu_allocator::MemoryLocation;
+
+use super::error::ReverieError;
+
+pub struct Buffer {
+    buffer: vk::Buffer,
+    allocation: Allocation,
+    size: u64,
+}
+
+impl Buffer {
+    pub fn new(device: &ash::Device, allocator: &mut Allocator, size: u64, usage: vk::BufferUsageFlags, location: MemoryLocation, name: &str) -> Result<Buffer, ReverieError> {
+        let buffer_create_info = vk::BufferCreateInfo::builder()
+            .size(size)
+            .usage(usage)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+        let buffer = unsafe { device.create_buffer(&buffer_create_info, None)? };
+
+        let mem_requirements = unsafe { device.get_buffer_memory_requirements(buffer) };
+
+        let allocation = match allocator.allocate(&AllocationCreateDesc {
+            requirements: mem_requirements,
+            location,
+            linear: true,
+            name
+        }) {
+            Ok(allocation) => allocation,
+            Err(err) => {
+                unsafe { device.destroy_buffer(buffer, None) };
+                return Err(err.into());
+            }
+        };
+
+        if let Err(err) = unsafe { device.bind_buffer_memory(buffer, allocation.memory(), allocation.offset()) } {
+            if let Err(free_err) = allocator.free(allocation) {
+                println!("[Reverie][error] Failed to free buffer memory: {}", free_err);
+            }
+            unsafe { device.destroy_buffer(buffer, None) };
+            return Err(err.into());
+        }
+
+        Ok(Buffer {
+            buffer,
+            allocation,
+            size
+        })
+    }
+
+    pub fn destroy(&mut self, device: &ash::Device, allocator: &mut Allocator) {
+        if let Err(err) = allocator.free(std::mem::take(&mut self.allocation)) {
+            println!("[Reverie][error] Failed to free buffer memory: {}", err);
+        }
+        unsafe {
+            device.destroy_buffer(self.buffer, None);
+        }
+    }
+
+    /// Host view of the buffer contents, only available for host visible memory locations.
+    pub fn mapped_slice(&self) -> Option<&[u8]> {
+        self.allocation.mapped_slice().map(|slice| &slice[..self.size as usize])
+    }
+
+    pub fn mapped_slice_mut(&mut self) -> Option<&mut [u8]> {
+        let size = self.size as usize;
+        self.allocation.mapped_slice_mut().map(|slice| &mut slice[..size])
+    }
+
+    pub fn get_buffer(&self) -> vk::Buffer { self.buffer }
+    pub fn get_size(&self) -> u64 { self.size }
+}
@@ -0,0 +1,195 @@
+use ash::vk;
+
+/// The world is right-handed with +y up. An unrotated camera looks down -z.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// `height` is the visible world height, the width follows from the aspect ratio.
+    Orthographic { height: f32, near: f32, far: f32 },
+    /// `fov_y` is the vertical field of view in radians.
+    Perspective { fov_y: f32, near: f32, far: f32 },
+}
+
+/// Turns world space into Vulkan's clip space for the shaders.
+///
+/// World space is y-up, while the 2D shaders used to write Vulkan's y-down clip space directly.
+/// The projection flips y, so scenes from before the camera existed show upside down, and a
+/// triangle wound counter-clockwise in world space is also counter-clockwise on screen. That is
+/// why `PipelineBuilder` treats counter-clockwise faces as front faces by default. Meshes wound
+/// for the old y-down space have to be reversed, or drawn with `front_face(vk::FrontFace::CLOCKWISE)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub projection: Projection,
+    pub aspect_ratio: f32,
+    pub position: uv::Vec3,
+    pub rotation: uv::Rotor3,
+}
+
+impl Default for Camera {
+    /// Shows the same [-1, 1] square the 2D shaders used to draw to in clip space, without stretching.
+    fn default() -> Self {
+        Self::orthographic(2.0, -1.0, 1.0)
+    }
+}
+
+impl Camera {
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: Projection::Orthographic { height, near, far },
+            aspect_ratio: 1.0,
+            position: uv::Vec3::zero(),
+            rotation: uv::Rotor3::identity()
+        }
+    }
+
+    pub fn perspective(fov_y: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: Projection::Perspective { fov_y, near, far },
+            ..Self::orthographic(2.0, near, far)
+        }
+    }
+
+    pub fn set_aspect_ratio(&mut self, extent: vk::Extent2D) {
+        if extent.height > 0 {
+            self.aspect_ratio = extent.width as f32 / extent.height as f32;
+        }
+    }
+
+    pub fn forward(&self) -> uv::Vec3 {
+        self.rotation * -uv::Vec3::unit_z()
+    }
+
+    pub fn right(&self) -> uv::Vec3 {
+        self.rotation * uv::Vec3::unit_x()
+    }
+
+    pub fn up(&self) -> uv::Vec3 {
+        self.rotation * uv::Vec3::unit_y()
+    }
+
+    /// Points the camera at `target` without rolling it.
+    pub fn look_at(&mut self, target: uv::Vec3) {
+        let direction = target - self.position;
+        if direction.mag_sq() <= f32::EPSILON {
+            return;
+        }
+
+        let direction = direction.normalized();
+        let yaw = direction.x.atan2(-direction.z);
+        let pitch = direction.y.clamp(-1.0, 1.0).asin();
+        self.rotation = rotation_from_yaw_pitch(yaw, pitch);
+    }
+
+    pub fn view(&self) -> uv::Mat4 {
+        self.rotation.reversed().into_matrix().into_homogeneous()
+            * uv::Mat4::from_translation(-self.position)
+    }
+
+    pub fn projection_matrix(&self) -> uv::Mat4 {
+        match self.projection {
+            Projection::Orthographic { height, near, far } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * self.aspect_ratio;
+                uv::projection::orthographic_vk(-half_width, half_width, -half_height, half_height, near, far)
+            },
+            Projection::Perspective { fov_y, near, far } => {
+                uv::projection::perspective_vk(fov_y, self.aspect_ratio, near, far)
+            }
+        }
+    }
+
+    pub fn view_projection(&self) -> uv::Mat4 {
+        self.projection_matrix() * self.view()
+    }
+}
+
+/// Positive yaw turns right (towards +x), positive pitch looks up (towards +y).
+pub fn rotation_from_yaw_pitch(yaw: f32, pitch: f32) -> uv::Rotor3 {
+    uv::Rotor3::from_rotation_xz(yaw) * uv::Rotor3::from_rotation_yz(pitch)
+}
+
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// Free-flying camera steered with a movement vector and mouse deltas.
+#[derive(Clone, Copy, Debug)]
+pub struct FirstPersonController {
+    pub yaw: f32,
+    pub pitch: f32,
+    /// World units per second.
+    pub move_speed: f32,
+    /// Radians per unit of look delta (usually pixels of mouse motion).
+    pub look_sensitivity: f32,
+}
+
+impl Default for FirstPersonController {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            move_speed: 2.0,
+            look_sensitivity: 0.002
+        }
+    }
+}
+
+impl FirstPersonController {
+    /// `movement` is relative to the camera: x strafes right, y moves up and z moves forward.
+    /// `look_delta` is the mouse motion, positive x turns right and positive y looks down.
+    pub fn update(&mut self, camera: &mut Camera, movement: uv::Vec3, look_delta: uv::Vec2, delta_time: f32) {
+        self.yaw += look_delta.x * self.look_sensitivity;
+        self.pitch = (self.pitch - look_delta.y * self.look_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        camera.rotation = rotation_from_yaw_pitch(self.yaw, self.pitch);
+
+        // Walk on the horizontal plane so looking up or down does not change the height
+        let forward = uv::Vec3::new(self.yaw.sin(), 0.0, -self.yaw.cos());
+        let right = uv::Vec3::new(self.yaw.cos(), 0.0, self.yaw.sin());
+        let direction = right * movement.x + uv::Vec3::unit_y() * movement.y + forward * movement.z;
+        if direction.mag_sq() > f32::EPSILON {
+            camera.position += direction.normalized() * self.move_speed * delta_time;
+        }
+    }
+}
+
+/// Keeps the camera on a sphere around `target`, looking at it.
+#[derive(Clone, Copy, Debug)]
+pub struct OrbitController {
+    pub target: uv::Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Radians per unit of rotate delta (usually pixels of mouse motion).
+    pub rotate_sensitivity: f32,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            target: uv::Vec3::zero(),
+            distance: 5.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+            rotate_sensitivity: 0.005
+        }
+    }
+}
+
+impl OrbitController {
+    /// Positive x turns the view to the right, positive y tilts it down.
+    pub fn rotate(&mut self, delta: uv::Vec2) {
+        self.yaw += delta.x * self.rotate_sensitivity;
+        self.pitch = (self.pitch - delta.y * self.rotate_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Positive amounts move the camera closer to the target.
+    pub fn zoom(&mut self, amount: f32) {
+        self.distance = (self.distance - amount).clamp(self.min_distance, self.max_distance);
+    }
+
+    pub fn update(&self, camera: &mut Camera) {
+        camera.rotation = rotation_from_yaw_pitch(self.yaw, self.pitch);
+        camera.position = self.target - camera.forward() * self.distance;
+    }
+}
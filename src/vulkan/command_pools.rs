@@ -1,5 +1,6 @@
 use ash::vk;
 use super::queue::QueueFamilies;
+use super::error::ReverieError;
 
 pub struct Pools {
     pub graphics_command_pool: vk::CommandPool,
@@ -7,24 +8,16 @@ pub struct Pools {
 }
 
 impl Pools {
-    pub fn new(logical_device: &ash::Device, queue_families: &QueueFamilies) -> Result<Pools, vk::Result> {
+    pub fn new(logical_device: &ash::Device, queue_families: &QueueFamilies) -> Result<Pools, ReverieError> {
         let graphics_command_pool_info = vk::CommandPoolCreateInfo::builder()
             .queue_family_index(queue_families.graphics.unwrap())
             .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER);
-        let graphics_command_pool = unsafe {
-            logical_device
-                .create_command_pool(&graphics_command_pool_info, None)
-                .expect("A problem with the command pool creation")
-        };
+        let graphics_command_pool = unsafe { logical_device.create_command_pool(&graphics_command_pool_info, None)? };
 
         let transfer_command_pool_info = vk::CommandPoolCreateInfo::builder()
             .queue_family_index(queue_families.transfer.unwrap())
             .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER);
-        let transfer_command_pool = unsafe {
-            logical_device
-                .create_command_pool(&transfer_command_pool_info, None)
-                .expect("A problem with the command pool creation")
-        };
+        let transfer_command_pool = unsafe { logical_device.create_command_pool(&transfer_command_pool_info, None)? };
 
         Ok(Pools {
             graphics_command_pool,
@@ -32,6 +25,39 @@ impl Pools {
         })
     }
 
+    /// Allocates and begins a command buffer meant to be submitted once with `end_single_time_commands`.
+    pub fn begin_single_time_commands(logical_device: &ash::Device, command_pool: vk::CommandPool) -> Result<vk::CommandBuffer, ReverieError> {
+        let commandbuffer_allocate_info = vk::CommandBufferAllocateInfo::builder()
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_pool(command_pool)
+            .command_buffer_count(1);
+        let command_buffer = unsafe { logical_device.allocate_command_buffers(&commandbuffer_allocate_info)? }[0];
+
+        let commandbuffer_begininfo = vk::CommandBufferBeginInfo::builder()
+            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+        unsafe { logical_device.begin_command_buffer(command_buffer, &commandbuffer_begininfo)? };
+
+        Ok(command_buffer)
+    }
+
+    /// Submits the command buffer, blocks until the queue is idle and frees it again.
+    pub fn end_single_time_commands(logical_device: &ash::Device, command_pool: vk::CommandPool, queue: vk::Queue, command_buffer: vk::CommandBuffer) -> Result<(), ReverieError> {
+        let command_buffers = [command_buffer];
+        let submit_info = [vk::SubmitInfo::builder()
+            .command_buffers(&command_buffers)
+            .build()
+        ];
+
+        unsafe {
+            logical_device.end_command_buffer(command_buffer)?;
+            logical_device.queue_submit(queue, &submit_info, vk::Fence::null())?;
+            logical_device.queue_wait_idle(queue)?;
+            logical_device.free_command_buffers(command_pool, &command_buffers);
+        }
+
+        Ok(())
+    }
+
     pub fn cleanup(&self, logical_device: &ash::Device) {
         unsafe {
             logical_device.destroy_command_pool(self.graphics_command_pool, None);
@@ -3,7 +3,7 @@ use ash::vk;
 
 use std::ffi;
 
-use anyhow::Result;
+use super::error::ReverieError;
 
 unsafe extern "system" fn vulkan_debug_utils_callback(
     message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
@@ -20,13 +20,15 @@ unsafe extern "system" fn vulkan_debug_utils_callback(
     vk::FALSE
 }
 
+/// Copies share the messenger, only one of them may be cleaned up.
+#[derive(Clone)]
 pub struct VulkanDebug {
     debug_utils: ext::DebugUtils,
     debug_messenger: vk::DebugUtilsMessengerEXT,
 }
 
 impl VulkanDebug {
-    pub fn new(entry: &ash::Entry, instance: &ash::Instance) -> Result<Self> {
+    pub fn new(entry: &ash::Entry, instance: &ash::Instance) -> Result<Self, ReverieError> {
         let debug_utils = ext::DebugUtils::new(entry, instance);
 
         let messenger_info = vk::DebugUtilsMessengerCreateInfoEXT {
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use ash::vk;
+
+use super::error::ReverieError;
+
+/// Identifies a descriptor set layout by its bindings, independent of the order they were listed in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct LayoutKey(Vec<(u32, vk::DescriptorType, u32, vk::ShaderStageFlags)>);
+
+impl LayoutKey {
+    fn new(bindings: &[vk::DescriptorSetLayoutBinding]) -> Self {
+        let mut key: Vec<_> = bindings
+            .iter()
+            .map(|binding| (binding.binding, binding.descriptor_type, binding.descriptor_count, binding.stage_flags))
+            .collect();
+        key.sort_by_key(|&(binding, ..)| binding);
+        LayoutKey(key)
+    }
+
+    fn bindings(&self) -> Vec<vk::DescriptorSetLayoutBinding> {
+        self.0
+            .iter()
+            .map(|&(binding, descriptor_type, descriptor_count, stage_flags)| vk::DescriptorSetLayoutBinding::builder()
+                .binding(binding)
+                .descriptor_type(descriptor_type)
+                .descriptor_count(descriptor_count)
+                .stage_flags(stage_flags)
+                .build())
+            .collect()
+    }
+}
+
+/// Creates every distinct descriptor set layout only once.
+///
+/// Pipelines and the resources bound to them ask the cache for their layouts, so equal bindings
+/// always end up with the same `vk::DescriptorSetLayout` and sets stay compatible between pipelines.
+pub struct DescriptorLayoutCache {
+    layouts: HashMap<LayoutKey, vk::DescriptorSetLayout>,
+}
+
+impl DescriptorLayoutCache {
+    pub fn new() -> Self {
+        Self {
+            layouts: HashMap::new()
+        }
+    }
+
+    /// Immutable samplers are not supported, `p_immutable_samplers` has to be null.
+    pub fn get_or_create(&mut self, logical_device: &ash::Device, bindings: &[vk::DescriptorSetLayoutBinding]) -> Result<vk::DescriptorSetLayout, ReverieError> {
+        let key = LayoutKey::new(bindings);
+        if let Some(&layout) = self.layouts.get(&key) {
+            return Ok(layout);
+        }
+
+        let layout_info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(bindings);
+        let layout = unsafe { logical_device.create_descriptor_set_layout(&layout_info, None)? };
+        self.layouts.insert(key, layout);
+
+        Ok(layout)
+    }
+
+    /// The bindings `layout` was created with, if it came from this cache.
+    pub fn bindings(&self, layout: vk::DescriptorSetLayout) -> Option<Vec<vk::DescriptorSetLayoutBinding>> {
+        self.layouts
+            .iter()
+            .find(|(_, &cached)| cached == layout)
+            .map(|(key, _)| key.bindings())
+    }
+
+    pub fn len(&self) -> usize {
+        self.layouts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layouts.is_empty()
+    }
+
+    pub fn destroy(&mut self, logical_device: &ash::Device) {
+        for (_, layout) in self.layouts.drain() {
+            unsafe { logical_device.destroy_descriptor_set_layout(layout, None) };
+        }
+    }
+}
+
+impl Default for DescriptorLayoutCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shorthand for a single descriptor at `binding`.
+pub fn layout_binding(binding: u32, descriptor_type: vk::DescriptorType, stage_flags: vk::ShaderStageFlags) -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding::builder()
+        .binding(binding)
+        .descriptor_type(descriptor_type)
+        .descriptor_count(1)
+        .stage_flags(stage_flags)
+        .build()
+}
+
+/// How many descriptors of each type a pool gets, per set it can hold.
+const POOL_RATIOS: [(vk::DescriptorType, f32); 4] = [
+    (vk::DescriptorType::UNIFORM_BUFFER, 2.0),
+    (vk::DescriptorType::STORAGE_BUFFER, 2.0),
+    (vk::DescriptorType::COMBINED_IMAGE_SAMPLER, 2.0),
+    (vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC, 1.0),
+];
+
+/// Hands out descriptor sets from a growing list of pools.
+///
+/// When a pool runs out a new one is created, so callers never have to size pools up front.
+/// Each frame in flight owns one allocator and resets it once the GPU is done with the frame,
+/// which frees all of its sets at once. Long lived sets, like the ones of textures, come from an
+/// allocator that is never reset.
+pub struct DescriptorAllocator {
+    sets_per_pool: u32,
+    current_pool: Option<vk::DescriptorPool>,
+    used_pools: Vec<vk::DescriptorPool>,
+    free_pools: Vec<vk::DescriptorPool>,
+}
+
+impl DescriptorAllocator {
+    pub fn new(sets_per_pool: u32) -> Self {
+        assert!(sets_per_pool > 0, "Descriptor pools need room for at least one set!");
+
+        Self {
+            sets_per_pool,
+            current_pool: None,
+            used_pools: vec![],
+            free_pools: vec![]
+        }
+    }
+
+    fn create_pool(&self, logical_device: &ash::Device) -> Result<vk::DescriptorPool, ReverieError> {
+        let pool_sizes: Vec<vk::DescriptorPoolSize> = POOL_RATIOS
+            .iter()
+            .map(|&(ty, ratio)| vk::DescriptorPoolSize {
+                ty,
+                descriptor_count: ((self.sets_per_pool as f32 * ratio) as u32).max(1)
+            })
+            .collect();
+
+        let pool_info = vk::DescriptorPoolCreateInfo::builder()
+            .pool_sizes(&pool_sizes)
+            .max_sets(self.sets_per_pool);
+        Ok(unsafe { logical_device.create_descriptor_pool(&pool_info, None)? })
+    }
+
+    fn grab_pool(&mut self, logical_device: &ash::Device) -> Result<vk::DescriptorPool, ReverieError> {
+        let pool = match self.free_pools.pop() {
+            Some(pool) => pool,
+            None => self.create_pool(logical_device)?
+        };
+        self.used_pools.push(pool);
+        self.current_pool = Some(pool);
+        Ok(pool)
+    }
+
+    pub fn allocate(&mut self, logical_device: &ash::Device, layout: vk::DescriptorSetLayout) -> Result<vk::DescriptorSet, ReverieError> {
+        let pool = match self.current_pool {
+            Some(pool) => pool,
+            None => self.grab_pool(logical_device)?
+        };
+
+        let set_layouts = [layout];
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(pool)
+            .set_layouts(&set_layouts);
+
+        match unsafe { logical_device.allocate_descriptor_sets(&allocate_info) } {
+            Ok(sets) => Ok(sets[0]),
+            // The pool is full, retry once with a fresh one
+            Err(vk::Result::ERROR_OUT_OF_POOL_MEMORY) | Err(vk::Result::ERROR_FRAGMENTED_POOL) => {
+                let pool = self.grab_pool(logical_device)?;
+                let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+                    .descriptor_pool(pool)
+                    .set_layouts(&set_layouts);
+                Ok(unsafe { logical_device.allocate_descriptor_sets(&allocate_info)? }[0])
+            },
+            Err(result) => Err(result.into())
+        }
+    }
+
+    /// Frees every set handed out so far. None of them may still be in use by the GPU.
+    pub fn reset(&mut self, logical_device: &ash::Device) -> Result<(), ReverieError> {
+        for pool in self.used_pools.drain(..) {
+            unsafe { logical_device.reset_descriptor_pool(pool, vk::DescriptorPoolResetFlags::empty())? };
+            self.free_pools.push(pool);
+        }
+        self.current_pool = None;
+        Ok(())
+    }
+
+    pub fn destroy(&mut self, logical_device: &ash::Device) {
+        for pool in self.used_pools.drain(..).chain(self.free_pools.drain(..)) {
+            unsafe { logical_device.destroy_descriptor_pool(pool, None) };
+        }
+        self.current_pool = None;
+    }
+}
+
+/// Collects descriptor writes for one set and applies them in a single `vkUpdateDescriptorSets` call.
+#[derive(Default)]
+pub struct DescriptorWriter {
+    buffer_infos: Vec<(u32, vk::DescriptorType, vk::DescriptorBufferInfo)>,
+    image_infos: Vec<(u32, vk::DescriptorType, vk::DescriptorImageInfo)>,
+}
+
+impl DescriptorWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_buffer(mut self, binding: u32, descriptor_type: vk::DescriptorType, buffer_info: vk::DescriptorBufferInfo) -> Self {
+        self.buffer_infos.push((binding, descriptor_type, buffer_info));
+        self
+    }
+
+    pub fn write_image(mut self, binding: u32, descriptor_type: vk::DescriptorType, image_info: vk::DescriptorImageInfo) -> Self {
+        self.image_infos.push((binding, descriptor_type, image_info));
+        self
+    }
+
+    pub fn update(&self, logical_device: &ash::Device, set: vk::DescriptorSet) {
+        // The infos are referenced by pointer, they live in `self` until the update is done
+        let buffer_writes = self.buffer_infos.iter().map(|(binding, descriptor_type, info)| {
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(*binding)
+                .dst_array_element(0)
+                .descriptor_type(*descriptor_type)
+                .buffer_info(std::slice::from_ref(info))
+                .build()
+        });
+        let image_writes = self.image_infos.iter().map(|(binding, descriptor_type, info)| {
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(*binding)
+                .dst_array_element(0)
+                .descriptor_type(*descriptor_type)
+                .image_info(std::slice::from_ref(info))
+                .build()
+        });
+        let writes: Vec<vk::WriteDescriptorSet> = buffer_writes.chain(image_writes).collect();
+
+        unsafe { logical_device.update_descriptor_sets(&writes, &[]) };
+    }
+}
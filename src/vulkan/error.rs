@@ -0,0 +1,83 @@
+use ash::vk;
+use gpu_allocator::AllocationError;
+
+/// Everything that can go wrong inside the renderer.
+///
+/// Raw `vk::Result`s are sorted into the variants an application may want to react to,
+/// like `DeviceLost` or the out of memory cases, everything else ends up in `Vulkan`.
+#[derive(Debug, thiserror::Error)]
+pub enum ReverieError {
+    #[error("No suitable physical device: {0}")]
+    DeviceSelection(String),
+    #[error("The device was lost")]
+    DeviceLost,
+    #[error("Out of host memory")]
+    OutOfHostMemory,
+    #[error("Out of device memory")]
+    OutOfDeviceMemory,
+    #[error("Memory allocation failed: {0}")]
+    Allocation(AllocationError),
+    #[error("Shader error: {0}")]
+    Shader(String),
+    /// The shaders expect a different vertex layout, push constants or descriptor sets than the pipeline provides.
+    #[error("Shader interface mismatch: {0}")]
+    InterfaceMismatch(String),
+    /// A pipeline needs a device feature that was not enabled, like `fillModeNonSolid` for wireframe.
+    #[error("Unsupported device feature: {0}")]
+    UnsupportedFeature(String),
+    #[error("Pipeline cache error: {0}")]
+    PipelineCache(String),
+    #[error("Swapchain error: {0}")]
+    Swapchain(vk::Result),
+    #[error("Surface error: {0}")]
+    Surface(vk::Result),
+    #[error("Buffer error: {0}")]
+    Buffer(String),
+    #[error("Frame readback failed: {0}")]
+    Readback(String),
+    #[error("Failed to create window: {0}")]
+    Window(#[from] winit::error::OsError),
+    #[error("Image error: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("File watcher error: {0}")]
+    Watcher(#[from] notify::Error),
+    #[error("Vulkan error: {0}")]
+    Vulkan(vk::Result),
+}
+
+impl From<vk::Result> for ReverieError {
+    fn from(result: vk::Result) -> Self {
+        match result {
+            vk::Result::ERROR_DEVICE_LOST => ReverieError::DeviceLost,
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => ReverieError::OutOfHostMemory,
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => ReverieError::OutOfDeviceMemory,
+            vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::SUBOPTIMAL_KHR => ReverieError::Swapchain(result),
+            vk::Result::ERROR_SURFACE_LOST_KHR | vk::Result::ERROR_NATIVE_WINDOW_IN_USE_KHR => ReverieError::Surface(result),
+            _ => ReverieError::Vulkan(result)
+        }
+    }
+}
+
+impl From<AllocationError> for ReverieError {
+    fn from(error: AllocationError) -> Self {
+        match error {
+            AllocationError::OutOfMemory => ReverieError::OutOfDeviceMemory,
+            _ => ReverieError::Allocation(error)
+        }
+    }
+}
+
+impl ReverieError {
+    /// Like `From<vk::Result>`, but results without a dedicated variant are turned into `fallback(result)`
+    /// instead of the generic `Vulkan` variant.
+    pub(crate) fn from_vk_or<F: FnOnce(vk::Result) -> ReverieError>(result: vk::Result, fallback: F) -> Self {
+        match ReverieError::from(result) {
+            ReverieError::Vulkan(result) => fallback(result),
+            error => error
+        }
+    }
+
+    pub fn is_out_of_memory(&self) -> bool {
+        matches!(self, ReverieError::OutOfHostMemory | ReverieError::OutOfDeviceMemory)
+    }
+}
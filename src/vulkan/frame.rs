@@ -0,0 +1,139 @@
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+use gpu_allocator::MemoryLocation;
+
+use super::buffer::Buffer;
+use super::descriptor::DescriptorAllocator;
+use super::uniform_buffer::UniformBuffer;
+use super::renderer::GlobalUniformData;
+use super::error::ReverieError;
+
+/// Per frame sets are short lived and few, a small pool is plenty before another one is needed.
+const FRAME_SETS_PER_POOL: u32 = 16;
+
+/// Everything the CPU needs to record and submit one frame while earlier frames are still on the GPU.
+///
+/// Frames are used round robin, independent of how many images the render target has.
+/// `in_flight` is signaled once the GPU is done with the frame, after that its command pool,
+/// descriptor sets, uniform buffer and transient buffers can be reused. `begin` waits for that
+/// and frees the old resources once, so resources added afterwards survive until the frame is
+/// submitted and finished again.
+pub struct FrameData {
+    pub command_pool: vk::CommandPool,
+    pub command_buffer: vk::CommandBuffer,
+    pub image_available: vk::Semaphore,
+    pub in_flight: vk::Fence,
+    /// Reset together with the command pool, sets allocated from it only live for one use of the frame.
+    pub descriptor_allocator: DescriptorAllocator,
+    pub global_uniforms: UniformBuffer<GlobalUniformData>,
+    transient_buffers: Vec<Buffer>,
+    /// Set by `begin`, cleared by `submitted`.
+    is_recording: bool,
+}
+
+impl FrameData {
+    pub fn new(logical_device: &ash::Device, allocator: &mut Allocator, graphics_family: u32) -> Result<FrameData, ReverieError> {
+        // Command buffers are re-recorded every frame, resetting the whole pool is cheaper than resetting each buffer
+        let command_pool_info = vk::CommandPoolCreateInfo::builder()
+            .queue_family_index(graphics_family)
+            .flags(vk::CommandPoolCreateFlags::TRANSIENT);
+        let command_pool = unsafe { logical_device.create_command_pool(&command_pool_info, None)? };
+
+        let commandbuffer_allocate_info = vk::CommandBufferAllocateInfo::builder()
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_pool(command_pool)
+            .command_buffer_count(1);
+        let command_buffer = unsafe { logical_device.allocate_command_buffers(&commandbuffer_allocate_info)? }[0];
+
+        let semaphore_info = vk::SemaphoreCreateInfo::builder();
+        let image_available = unsafe { logical_device.create_semaphore(&semaphore_info, None)? };
+
+        // Created signaled so the first wait on a fresh frame returns immediately
+        let fence_info = vk::FenceCreateInfo::builder().flags(vk::FenceCreateFlags::SIGNALED);
+        let in_flight = unsafe { logical_device.create_fence(&fence_info, None)? };
+
+        let global_uniforms = UniformBuffer::new(logical_device, allocator, "Global Uniform Buffer")?;
+
+        Ok(FrameData {
+            command_pool,
+            command_buffer,
+            image_available,
+            in_flight,
+            descriptor_allocator: DescriptorAllocator::new(FRAME_SETS_PER_POOL),
+            global_uniforms,
+            transient_buffers: vec![],
+            is_recording: false
+        })
+    }
+
+    pub fn create_frames(logical_device: &ash::Device, allocator: &mut Allocator, graphics_family: u32, count: usize) -> Result<Vec<FrameData>, ReverieError> {
+        (0..count)
+            .map(|_| Self::new(logical_device, allocator, graphics_family))
+            .collect()
+    }
+
+    /// Blocks until the GPU has finished the last submission of this frame.
+    pub fn wait(&self, logical_device: &ash::Device) -> Result<(), ReverieError> {
+        unsafe { logical_device.wait_for_fences(&[self.in_flight], true, std::u64::MAX)? };
+        Ok(())
+    }
+
+    /// Makes the frame ready to be filled for its next submission: waits for the previous one to
+    /// finish and frees what it used. Does nothing if that already happened since the last submission.
+    pub fn begin(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) -> Result<(), ReverieError> {
+        if self.is_recording {
+            return Ok(());
+        }
+
+        self.wait(logical_device)?;
+        self.reset(logical_device, allocator)?;
+        self.is_recording = true;
+        Ok(())
+    }
+
+    /// Marks the frame as handed to the GPU, the next `begin` waits for it again.
+    pub fn submitted(&mut self) {
+        self.is_recording = false;
+    }
+
+    /// Frees the transient resources and descriptor sets of the previous use and resets the command buffer.
+    /// Only call this after `wait`.
+    fn reset(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) -> Result<(), ReverieError> {
+        for buffer in &mut self.transient_buffers {
+            buffer.destroy(logical_device, allocator);
+        }
+        self.transient_buffers.clear();
+
+        self.descriptor_allocator.reset(logical_device)?;
+        unsafe { logical_device.reset_command_pool(self.command_pool, vk::CommandPoolResetFlags::empty())? };
+        Ok(())
+    }
+
+    /// Creates a host visible buffer that lives until the next submission of this frame has
+    /// finished on the GPU.
+    pub fn create_transient_buffer(&mut self, logical_device: &ash::Device, allocator: &mut Allocator, size: u64, usage: vk::BufferUsageFlags) -> Result<&mut Buffer, ReverieError> {
+        self.begin(logical_device, allocator)?;
+        let buffer = Buffer::new(logical_device, allocator, size, usage, MemoryLocation::CpuToGpu, "Transient Buffer")?;
+        self.transient_buffers.push(buffer);
+        Ok(self.transient_buffers.last_mut().unwrap())
+    }
+
+    /// The buffers created by `create_transient_buffer` for the current or last submission.
+    pub fn transient_buffers(&self) -> &[Buffer] {
+        &self.transient_buffers
+    }
+
+    pub fn destroy(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        for buffer in &mut self.transient_buffers {
+            buffer.destroy(logical_device, allocator);
+        }
+        self.transient_buffers.clear();
+        self.global_uniforms.destroy(logical_device, allocator);
+        self.descriptor_allocator.destroy(logical_device);
+        unsafe {
+            logical_device.destroy_fence(self.in_flight, None);
+            logical_device.destroy_semaphore(self.image_available, None);
+            logical_device.destroy_command_pool(self.command_pool, None);
+        }
+    }
+}
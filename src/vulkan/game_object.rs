@@ -1,39 +1,257 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use uv::{Lerp, Slerp};
 
 use super::mesh::Mesh;
+use super::texture::TextureHandle;
+use super::pipeline::PipelineHandle;
+use super::scene;
 
-static OBJECT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+use crate::ecs::entity::Entity;
+use crate::ecs::world::World;
 
+/// The components of a drawable object, added to a `World` by `spawn`.
+///
+/// The renderer draws every entity with a `Mesh` and either a `Transform2DComponent` or a
+/// `Transform3DComponent`, using its `Color`, `TextureHandle` and `PipelineHandle` if it has them.
 pub struct GameObject {
-    id: usize,
+    id: Entity,
     pub mesh: Mesh,
+    /// An sRGB color, converted to linear before it reaches the shaders, see `utils::color`.
     pub color: uv::Vec3,
-    pub transform2d: Transform2DComponent
+    /// Sampled across the mesh' texture coordinates and tinted by `color`. Only used by 2D objects.
+    pub texture: Option<TextureHandle>,
+    /// Replaces the default pipeline for this object. It has to take the same push constants
+    /// and descriptor sets as the default, see `VulkanRenderer::pipeline_builder_2d`.
+    pub pipeline: Option<PipelineHandle>,
+    pub transform2d: Transform2DComponent,
+    /// Set for objects with a `Vertex3D` mesh, which are drawn with the 3D pipeline instead.
+    pub transform3d: Option<Transform3DComponent>,
+    /// Attaches the object to this entity when it is spawned, the transforms are then relative to it.
+    pub parent: Option<Entity>
 }
 
 impl GameObject {
     pub fn new(mesh: Mesh, color: uv::Vec3) -> Self {
         Self {
-            id: OBJECT_COUNTER.fetch_add(1, Ordering::SeqCst),
+            id: Entity::next(),
             mesh,
             color,
-            transform2d: Transform2DComponent {
-                translation: uv::Vec2::default()
-            }
+            texture: None,
+            pipeline: None,
+            transform2d: Transform2DComponent::default(),
+            transform3d: None,
+            parent: None
+        }
+    }
+
+    pub fn new_3d(mesh: Mesh, color: uv::Vec3) -> Self {
+        Self {
+            transform3d: Some(Transform3DComponent::default()),
+            ..Self::new(mesh, color)
+        }
+    }
+
+    /// A 2D object drawing `texture` on its mesh, left untinted.
+    pub fn new_textured(mesh: Mesh, texture: TextureHandle) -> Self {
+        Self {
+            texture: Some(texture),
+            ..Self::new(mesh, uv::Vec3::one())
         }
     }
 
     pub fn get_id(&self) -> usize {
+        self.id.id()
+    }
+
+    /// The entity the object becomes when it is spawned.
+    pub fn entity(&self) -> Entity {
         self.id
     }
+
+    /// Adds the object to `world` as its components. Gameplay data can be added to the returned
+    /// entity as further components.
+    pub fn spawn(self, world: &mut World) -> Entity {
+        let entity = self.id;
+        world.spawn_entity(entity);
+        world.insert(entity, self.mesh);
+        world.insert(entity, Color(self.color));
+        match self.transform3d {
+            Some(transform3d) => { world.insert(entity, transform3d); },
+            None => { world.insert(entity, self.transform2d); }
+        }
+        if let Some(texture) = self.texture {
+            world.insert(entity, texture);
+        }
+        if let Some(pipeline) = self.pipeline {
+            world.insert(entity, pipeline);
+        }
+        if let Some(parent) = self.parent {
+            scene::attach(world, entity, parent);
+        }
+        entity
+    }
 }
 
+/// Tints the object. An sRGB color, converted to linear before it reaches the shaders, see `utils::color`.
+/// Objects without one are drawn white.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color(pub uv::Vec3);
+
+/// The value a component had before the last fixed step. Entities that have one are drawn
+/// blended between it and the current value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Previous<T>(pub T);
+
+/// Components that can be drawn in between two fixed steps.
+pub trait Interpolate: Copy + 'static {
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for Transform2DComponent {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+impl Interpolate for Transform3DComponent {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+/// Remembers the current `T` of every entity as its `Previous<T>`. The runner calls this before
+/// every fixed step.
+pub fn save_previous<T: Interpolate>(world: &mut World) {
+    let current: Vec<(Entity, T)> = world.query::<T>().map(|(entity, &component)| (entity, component)).collect();
+    for (entity, component) in current {
+        world.insert(entity, Previous(component));
+    }
+}
+
+/// The entity's `T` `alpha` of the way from the previous fixed step to the current one.
+pub fn interpolated<T: Interpolate>(world: &World, entity: Entity, alpha: f32) -> Option<T> {
+    let current = world.get::<T>(entity)?;
+    match world.get::<Previous<T>>(entity) {
+        Some(Previous(previous)) => Some(previous.interpolate(current, alpha)),
+        None => Some(*current)
+    }
+}
+
+/// Draws the entity at its current transforms until the next fixed step, so it jumps instead
+/// of sliding after being teleported.
+pub fn reset_interpolation(world: &mut World, entity: Entity) {
+    world.remove::<Previous<Transform2DComponent>>(entity);
+    world.remove::<Previous<Transform3DComponent>>(entity);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Transform2DComponent {
     pub translation: uv::Vec2,
+    /// Rotation in radians, positive values turn the +x axis towards +y.
+    pub rotation: f32,
+    pub scale: uv::Vec2,
+    /// Layer in [0, 1], smaller values are drawn in front of larger ones.
+    pub depth: f32,
+}
+
+impl Default for Transform2DComponent {
+    fn default() -> Self {
+        Self {
+            translation: uv::Vec2::zero(),
+            rotation: 0.0,
+            scale: uv::Vec2::one(),
+            depth: 0.0
+        }
+    }
 }
 
 impl Transform2DComponent {
+    /// Scales first, then rotates. Translation is applied separately through the push constant offset.
     pub fn mat2(&self) -> uv::Mat2 {
-        uv::Mat2::identity()
+        let (sin, cos) = self.rotation.sin_cos();
+        let rotation = uv::Mat2::new(
+            uv::Vec2::new(cos, sin),
+            uv::Vec2::new(-sin, cos)
+        );
+        let scale = uv::Mat2::new(
+            uv::Vec2::new(self.scale.x, 0.0),
+            uv::Vec2::new(0.0, self.scale.y)
+        );
+
+        rotation * scale
+    }
+
+    /// Turns the object so its local +x axis points at `target`.
+    pub fn look_at(&mut self, target: uv::Vec2) {
+        let direction = target - self.translation;
+        if direction.mag_sq() > f32::EPSILON {
+            self.rotation = direction.y.atan2(direction.x);
+        }
+    }
+
+    /// Blends towards `to` by `t` in [0, 1]. The rotation is not wrapped, an object spinning
+    /// past a full turn keeps spinning the same way.
+    pub fn lerp(&self, to: &Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(to.translation, t),
+            rotation: self.rotation + (to.rotation - self.rotation) * t,
+            scale: self.scale.lerp(to.scale, t),
+            depth: self.depth + (to.depth - self.depth) * t
+        }
+    }
+
+    /// Orbits the object around `pivot` by `angle` radians, turning it along with the orbit.
+    pub fn rotate_around(&mut self, pivot: uv::Vec2, angle: f32) {
+        let (sin, cos) = angle.sin_cos();
+        let offset = self.translation - pivot;
+        self.translation = pivot + uv::Vec2::new(
+            offset.x * cos - offset.y * sin,
+            offset.x * sin + offset.y * cos
+        );
+        self.rotation += angle;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform3DComponent {
+    pub translation: uv::Vec3,
+    /// Unit rotor, ultraviolet's equivalent of a unit quaternion.
+    /// Use `uv::Rotor3::from_quaternion_array` to convert from `[x, y, z, w]`.
+    pub rotation: uv::Rotor3,
+    pub scale: uv::Vec3,
+}
+
+impl Default for Transform3DComponent {
+    fn default() -> Self {
+        Self {
+            translation: uv::Vec3::zero(),
+            rotation: uv::Rotor3::identity(),
+            scale: uv::Vec3::one()
+        }
+    }
+}
+
+impl Transform3DComponent {
+    /// Model matrix applying scale, then rotation, then translation.
+    pub fn mat4(&self) -> uv::Mat4 {
+        uv::Mat4::from_translation(self.translation)
+            * self.rotation.into_matrix().into_homogeneous()
+            * uv::Mat4::from_nonuniform_scale(self.scale)
+    }
+
+    /// Blends towards `to` by `t` in [0, 1], turning along the shorter arc.
+    pub fn lerp(&self, to: &Self, t: f32) -> Self {
+        // Rotors q and -q are the same rotation, pick the one closer to the start
+        let end_rotation = if self.rotation.dot(to.rotation) < 0.0 { to.rotation * -1.0 } else { to.rotation };
+        Self {
+            translation: self.translation.lerp(to.translation, t),
+            rotation: self.rotation.slerp(end_rotation, t).normalized(),
+            scale: self.scale.lerp(to.scale, t)
+        }
+    }
+
+    /// Inverse transpose of the model matrix' upper 3x3, keeps normals perpendicular under non-uniform scale.
+    pub fn normal_matrix(&self) -> uv::Mat3 {
+        self.rotation.into_matrix()
+            * uv::Mat3::from_nonuniform_scale(uv::Vec3::one() / self.scale)
     }
 }
\ No newline at end of file
@@ -0,0 +1,134 @@
+use ash::vk;
+use gpu_allocator::vulkan::*;
+use gpu_allocator::MemoryLocation;
+
+use super::error::ReverieError;
+
+pub struct Image {
+    pub image: vk::Image,
+    pub imageview: vk::ImageView,
+    pub format: vk::Format,
+    pub extent: vk::Extent2D,
+    pub mip_levels: u32,
+    allocation: Allocation,
+}
+
+impl Image {
+    pub fn new(
+        device: &ash::Device,
+        allocator: &mut Allocator,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+        aspect_mask: vk::ImageAspectFlags,
+        name: &str,
+    ) -> Result<Image, ReverieError> {
+        Self::with_mip_levels(device, allocator, extent, format, usage, aspect_mask, 1, name)
+    }
+
+    /// Like `new`, but the image and its view cover `mip_levels` levels.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_mip_levels(
+        device: &ash::Device,
+        allocator: &mut Allocator,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+        aspect_mask: vk::ImageAspectFlags,
+        mip_levels: u32,
+        name: &str,
+    ) -> Result<Image, ReverieError> {
+        let image_create_info = vk::ImageCreateInfo::builder()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(format)
+            .extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1
+            })
+            .mip_levels(mip_levels)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(usage)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+
+        let image = unsafe { device.create_image(&image_create_info, None)? };
+
+        let mem_requirements = unsafe { device.get_image_memory_requirements(image) };
+
+        let allocation = match allocator.allocate(&AllocationCreateDesc {
+            requirements: mem_requirements,
+            location: MemoryLocation::GpuOnly,
+            linear: false,
+            name
+        }) {
+            Ok(allocation) => allocation,
+            Err(err) => {
+                unsafe { device.destroy_image(image, None) };
+                return Err(err.into());
+            }
+        };
+
+        // Everything created so far is destroyed again if the image can't be finished
+        let fail = |allocator: &mut Allocator, allocation: Allocation, err: vk::Result| {
+            if let Err(free_err) = allocator.free(allocation) {
+                println!("[Reverie][error] Failed to free image memory: {}", free_err);
+            }
+            unsafe { device.destroy_image(image, None) };
+            ReverieError::from(err)
+        };
+
+        if let Err(err) = unsafe { device.bind_image_memory(image, allocation.memory(), allocation.offset()) } {
+            return Err(fail(allocator, allocation, err));
+        }
+
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(aspect_mask)
+            .base_mip_level(0)
+            .level_count(mip_levels)
+            .base_array_layer(0)
+            .layer_count(1);
+        let imageview_create_info = vk::ImageViewCreateInfo::builder()
+            .image(image)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(format)
+            .subresource_range(*subresource_range);
+        let imageview = match unsafe { device.create_image_view(&imageview_create_info, None) } {
+            Ok(imageview) => imageview,
+            Err(err) => return Err(fail(allocator, allocation, err))
+        };
+
+        Ok(Image {
+            image,
+            imageview,
+            format,
+            extent,
+            mip_levels,
+            allocation
+        })
+    }
+
+    /// Creates a depth attachment matching `extent`, including the stencil aspect for combined formats.
+    pub fn new_depth(device: &ash::Device, allocator: &mut Allocator, extent: vk::Extent2D, format: vk::Format) -> Result<Image, ReverieError> {
+        let aspect_mask = match format {
+            vk::Format::D32_SFLOAT_S8_UINT | vk::Format::D24_UNORM_S8_UINT | vk::Format::D16_UNORM_S8_UINT => {
+                vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+            },
+            _ => vk::ImageAspectFlags::DEPTH
+        };
+
+        Self::new(device, allocator, extent, format, vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT, aspect_mask, "Depth Image")
+    }
+
+    pub fn destroy(&mut self, device: &ash::Device, allocator: &mut Allocator) {
+        unsafe {
+            device.destroy_image_view(self.imageview, None);
+            device.destroy_image(self.image, None);
+        }
+        if let Err(err) = allocator.free(std::mem::take(&mut self.allocation)) {
+            println!("[Reverie][error] Failed to free image memory: {}", err);
+        }
+    }
+}
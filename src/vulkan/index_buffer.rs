@@ -2,52 +2,69 @@ use ash::vk;
 use gpu_allocator::vulkan::*;
 use gpu_allocator::MemoryLocation;
 
+use super::error::ReverieError;
+
 pub struct IndexBuffer {
     buffer: vk::Buffer,
     allocation: Allocation,
+    size: u64,
     index_count: u32
 }
 
 impl IndexBuffer {
-    pub fn new(device: &ash::Device, allocator: &mut Allocator, size: u64) -> IndexBuffer {
+    pub fn new(device: &ash::Device, allocator: &mut Allocator, size: u64) -> Result<IndexBuffer, ReverieError> {
+        Self::create(device, allocator, size, vk::BufferUsageFlags::INDEX_BUFFER, MemoryLocation::CpuToGpu)
+    }
+
+    /// Creates a buffer in device local memory. It can't be written through `update_buffer`,
+    /// fill it with `Uploader::upload_buffer` and `set_index_count` instead.
+    pub fn new_device_local(device: &ash::Device, allocator: &mut Allocator, size: u64) -> Result<IndexBuffer, ReverieError> {
+        Self::create(device, allocator, size, vk::BufferUsageFlags::INDEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST, MemoryLocation::GpuOnly)
+    }
+
+    fn create(device: &ash::Device, allocator: &mut Allocator, size: u64, usage: vk::BufferUsageFlags, location: MemoryLocation) -> Result<IndexBuffer, ReverieError> {
         let index_buffer_create_info = vk::BufferCreateInfo::builder()
             .size(size)
-            .usage(vk::BufferUsageFlags::INDEX_BUFFER)
+            .usage(usage)
             .sharing_mode(vk::SharingMode::EXCLUSIVE);
 
-        let index_buffer = unsafe {
-            device
-                .create_buffer(&index_buffer_create_info, None)
-                .expect("Failed to create index buffer")
-        };
+        let index_buffer = unsafe { device.create_buffer(&index_buffer_create_info, None)? };
 
         let mem_requirements = unsafe { device.get_buffer_memory_requirements(index_buffer) };
-        let location = MemoryLocation::CpuToGpu;
 
-        let allocation = allocator.allocate(&AllocationCreateDesc {
+        let allocation = match allocator.allocate(&AllocationCreateDesc {
             requirements: mem_requirements,
             location,
             linear: true,
             name: "Index Buffer"
-        }).expect("Failed to allocate memory for index buffer!");
+        }) {
+            Ok(allocation) => allocation,
+            Err(err) => {
+                unsafe { device.destroy_buffer(index_buffer, None) };
+                return Err(err.into());
+            }
+        };
 
-        unsafe {
-            device
-                .bind_buffer_memory(index_buffer, allocation.memory(), allocation.offset())
-                .expect("Failed to bind index buffer");
+        if let Err(err) = unsafe { device.bind_buffer_memory(index_buffer, allocation.memory(), allocation.offset()) } {
+            if let Err(free_err) = allocator.free(allocation) {
+                println!("[Reverie][error] Failed to free index buffer memory: {}", free_err);
+            }
+            unsafe { device.destroy_buffer(index_buffer, None) };
+            return Err(err.into());
         }
 
-        IndexBuffer {
+        Ok(IndexBuffer {
             buffer: index_buffer,
             allocation,
+            size,
             index_count: 0
-        }
+        })
     }
 
     pub fn destroy(&mut self, device: &ash::Device, allocator: &mut Allocator) {
-        allocator
-            .free(std::mem::take(&mut self.allocation))
-            .expect("Failed to free index buffer memory!");
+        if let Err(err) = allocator.free(std::mem::take(&mut self.allocation)) {
+            println!("[Reverie][error] Failed to free index buffer memory: {}", err);
+        }
         unsafe {
             device.destroy_buffer(self.buffer, None);
         }
@@ -57,13 +74,26 @@ impl IndexBuffer {
         (index_count * std::mem::size_of::<u32>()) as u64
     }
 
-    pub fn update_buffer(&mut self, data: &[u32]) {
-        let dst = self.allocation.mapped_ptr().unwrap().cast().as_ptr();
-        unsafe {
-            std::ptr::copy_nonoverlapping(data.as_ptr(), dst, data.len())
+    pub fn update_buffer(&mut self, data: &[u32]) -> Result<(), ReverieError> {
+        let data_size = std::mem::size_of_val(data) as u64;
+        if data_size > self.size {
+            return Err(ReverieError::Buffer(format!("index data needs {} bytes but the buffer only holds {}", data_size, self.size)));
         }
+        let dst = self.allocation.mapped_ptr()
+            .ok_or_else(|| ReverieError::Buffer("index buffer is not host visible, use the Uploader for device local buffers".into()))?
+            .cast().as_ptr();
 
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), dst, data.len());
+        }
         self.index_count = data.len() as u32;
+
+        Ok(())
+    }
+
+    /// Sets the number of indices drawn after the contents were written by an upload.
+    pub fn set_index_count(&mut self, index_count: u32) {
+        self.index_count = index_count;
     }
 
     pub fn get_buffer(&self) -> vk::Buffer { self.buffer }
@@ -1,12 +1,14 @@
 use ash::vk;
 
 use super::queue::*;
+use super::error::ReverieError;
 
 pub struct LogicalDevice {}
 
 impl LogicalDevice {
-    pub fn new(instance: &ash::Instance, physical_device: vk::PhysicalDevice, queue_families: &QueueFamilies, layer_names: &[&str]
-    ) -> Result<(ash::Device, Queues), vk::Result> {
+    pub fn new(instance: &ash::Instance, physical_device: vk::PhysicalDevice, queue_families: &QueueFamilies, layer_names: &[&str], device_extensions: &[&std::ffi::CStr],
+        enabled_features: &vk::PhysicalDeviceFeatures
+    ) -> Result<(ash::Device, Queues), ReverieError> {
         let layer_names_c: Vec<std::ffi::CString> = layer_names
             .iter()
             .map(|&ln| std::ffi::CString::new(ln).unwrap())
@@ -18,25 +20,29 @@ impl LogicalDevice {
 
         let priorities = [1.0f32];
 
-        let queue_infos = [
+        let mut queue_infos = vec![
             vk::DeviceQueueCreateInfo::builder()
                 .queue_family_index(queue_families.graphics.unwrap())
                 .queue_priorities(&priorities)
-                .build(),
-            vk::DeviceQueueCreateInfo::builder()
-                .queue_family_index(queue_families.transfer.unwrap())
-                .queue_priorities(&priorities)
                 .build()
         ];
+        // Software implementations like lavapipe expose a single family that does everything
+        if queue_families.transfer != queue_families.graphics {
+            queue_infos.push(vk::DeviceQueueCreateInfo::builder()
+                .queue_family_index(queue_families.transfer.unwrap())
+                .queue_priorities(&priorities)
+                .build());
+        }
 
-        let device_extension_name_pointers: Vec<*const i8> = 
-            vec![
-                ash::extensions::khr::Swapchain::name().as_ptr()
-            ];
+        let device_extension_name_pointers: Vec<*const i8> = device_extensions
+            .iter()
+            .map(|ext| ext.as_ptr())
+            .collect();
         
         let device_create_info = vk::DeviceCreateInfo::builder()
             .queue_create_infos(&queue_infos)
             .enabled_extension_names(&device_extension_name_pointers)
+            .enabled_features(enabled_features)
             .enabled_layer_names(&layer_name_pointers);
         
         let logical_device = unsafe { instance.create_device(physical_device, &device_create_info, None)? };
@@ -3,7 +3,9 @@ use gpu_allocator::vulkan::Allocator;
 
 use super::vertex_buffer::VertexBuffer;
 use super::index_buffer::IndexBuffer;
-use super::vertex::Vertex;
+use super::vertex::{Vertex, Vertex3D};
+use super::upload::Uploader;
+use super::error::ReverieError;
 
 pub struct Mesh {
     pub vertex_buffers: Vec<VertexBuffer>,
@@ -11,12 +13,21 @@ pub struct Mesh {
 }
 
 impl Mesh {
-    pub fn new(device: &ash::Device, allocator: &mut Allocator, vertex_count: usize, index_count: usize) -> Result<Self, vk::Result> {
+    pub fn new(device: &ash::Device, allocator: &mut Allocator, vertex_count: usize, index_count: usize) -> Result<Self, ReverieError> {
+        Self::with_vertex_size(device, allocator, VertexBuffer::get_vertex_buffer_size::<Vertex>(vertex_count), index_count)
+    }
+
+    /// Creates a mesh holding `Vertex3D` data, to be drawn with a 3D game object.
+    pub fn new_3d(device: &ash::Device, allocator: &mut Allocator, vertex_count: usize, index_count: usize) -> Result<Self, ReverieError> {
+        Self::with_vertex_size(device, allocator, VertexBuffer::get_vertex_buffer_size::<Vertex3D>(vertex_count), index_count)
+    }
+
+    fn with_vertex_size(device: &ash::Device, allocator: &mut Allocator, vertex_buffer_size: u64, index_count: usize) -> Result<Self, ReverieError> {
         let mut vertex_buffers = vec![];
-        let vertex_buffer = VertexBuffer::new(device, allocator, VertexBuffer::get_vertex_buffer_size(vertex_count));
+        let vertex_buffer = VertexBuffer::new(device, allocator, vertex_buffer_size)?;
         vertex_buffers.push(vertex_buffer);
         if index_count > 0 {
-            let index_buffer = IndexBuffer::new(device, allocator, IndexBuffer::get_index_buffer_size(index_count));
+            let index_buffer = IndexBuffer::new(device, allocator, IndexBuffer::get_index_buffer_size(index_count))?;
             Ok(Self {
                 vertex_buffers,
                 index_buffer: Some(index_buffer)
@@ -29,18 +40,64 @@ impl Mesh {
         }
     }
 
-    pub fn update_vertex_buffer(&mut self, data: &[Vertex]) {
-        self.vertex_buffers[0].update_buffer(data);
+    /// Creates a mesh whose data lives in device local memory. The copies are only recorded,
+    /// they complete once the uploader is flushed.
+    ///
+    /// If anything fails the buffers are destroyed and all copies the uploader has not flushed yet
+    /// are discarded, since they could point at the destroyed buffers.
+    pub fn new_static<V: Copy>(device: &ash::Device, allocator: &mut Allocator, uploader: &mut Uploader, vertices: &[V], indices: &[u32]) -> Result<Self, ReverieError> {
+        // A buffer of size zero is invalid
+        if vertices.is_empty() {
+            return Err(ReverieError::Buffer("static mesh needs at least one vertex".into()));
+        }
+
+        let mut vertex_buffer = VertexBuffer::new_device_local(device, allocator, VertexBuffer::get_vertex_buffer_size::<V>(vertices.len()))?;
+        let mut index_buffer = if indices.is_empty() {
+            None
+        } else {
+            match IndexBuffer::new_device_local(device, allocator, IndexBuffer::get_index_buffer_size(indices.len())) {
+                Ok(index_buffer) => Some(index_buffer),
+                Err(err) => {
+                    vertex_buffer.destroy(device, allocator);
+                    return Err(err);
+                }
+            }
+        };
+
+        // Recorded last, once both buffers exist
+        let uploaded = uploader.upload_buffer(device, allocator, vertices, vertex_buffer.get_buffer(), vk::PipelineStageFlags::VERTEX_INPUT, vk::AccessFlags::VERTEX_ATTRIBUTE_READ)
+            .and_then(|()| match &index_buffer {
+                Some(index_buffer) => uploader.upload_buffer(device, allocator, indices, index_buffer.get_buffer(), vk::PipelineStageFlags::VERTEX_INPUT, vk::AccessFlags::INDEX_READ),
+                None => Ok(())
+            });
+        if let Err(err) = uploaded {
+            uploader.discard(device, allocator);
+            vertex_buffer.destroy(device, allocator);
+            if let Some(index_buffer) = &mut index_buffer {
+                index_buffer.destroy(device, allocator);
+            }
+            return Err(err);
+        }
+
+        vertex_buffer.set_vertex_count(vertices.len() as u32);
+        if let Some(index_buffer) = &mut index_buffer {
+            index_buffer.set_index_count(indices.len() as u32);
+        }
+
+        Ok(Self {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer
+        })
+    }
+
+    pub fn update_vertex_buffer<V: Copy>(&mut self, data: &[V]) -> Result<(), ReverieError> {
+        self.vertex_buffers[0].update_buffer(data)
     }
 
-    pub fn update_index_buffer(&mut self, data: &[u32]) {
+    pub fn update_index_buffer(&mut self, data: &[u32]) -> Result<(), ReverieError> {
         match self.index_buffer {
-            Some(ref mut index_buffer) => {
-                index_buffer.update_buffer(data);
-            },
-            None => {
-                println!("No index buffer on mesh");
-            }
+            Some(ref mut index_buffer) => index_buffer.update_buffer(data),
+            None => Err(ReverieError::Buffer("mesh has no index buffer".into()))
         }
     }
 
@@ -13,4 +13,22 @@ pub mod vertex_buffer;
 pub mod index_buffer;
 pub mod mesh;
 pub mod surface;
-pub mod game_object;
\ No newline at end of file
+pub mod game_object;
+pub mod image;
+pub mod offscreen;
+pub mod render_target;
+pub mod buffer;
+pub mod screenshot;
+pub mod camera;
+pub mod upload;
+pub mod frame;
+pub mod error;
+pub mod texture;
+pub mod descriptor;
+pub mod uniform_buffer;
+pub mod storage_buffer;
+pub mod shader;
+pub mod reflection;
+pub mod pipeline_cache;
+pub mod output;
+pub mod scene;
\ No newline at end of file
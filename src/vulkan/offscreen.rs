@@ -0,0 +1,89 @@
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+
+use super::image::Image;
+use super::error::ReverieError;
+
+/// Color targets used in place of a swapchain when rendering without a window.
+pub struct OffscreenTarget {
+    pub images: Vec<Image>,
+    pub depth_images: Vec<Image>,
+    pub framebuffers: Vec<vk::Framebuffer>,
+    pub format: vk::Format,
+    pub extent: vk::Extent2D,
+    pub image_count: usize,
+    pub current_image: usize,
+    pub rendered_image: Option<usize>,
+}
+
+impl OffscreenTarget {
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut Allocator,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        image_count: usize,
+    ) -> Result<OffscreenTarget, ReverieError> {
+        let mut images = Vec::with_capacity(image_count);
+        for _ in 0..image_count {
+            let image = Image::new(
+                logical_device,
+                allocator,
+                extent,
+                format,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+                vk::ImageAspectFlags::COLOR,
+                "Offscreen Image"
+            )?;
+            images.push(image);
+        }
+
+        Ok(OffscreenTarget {
+            images,
+            depth_images: vec![],
+            framebuffers: vec![],
+            format,
+            extent,
+            image_count,
+            current_image: 0,
+            rendered_image: None
+        })
+    }
+
+    pub fn create_depth_images(&mut self, logical_device: &ash::Device, allocator: &mut Allocator, depth_format: vk::Format) -> Result<(), ReverieError> {
+        for _ in 0..self.image_count {
+            let depth_image = Image::new_depth(logical_device, allocator, self.extent, depth_format)?;
+            self.depth_images.push(depth_image);
+        }
+
+        Ok(())
+    }
+
+    pub fn create_framebuffers(&mut self, logical_device: &ash::Device, renderpass: vk::RenderPass) -> Result<(), ReverieError> {
+        for (image, depth_image) in self.images.iter().zip(&self.depth_images) {
+            let iview = [image.imageview, depth_image.imageview];
+            let framebuffer_info = vk::FramebufferCreateInfo::builder()
+                .render_pass(renderpass)
+                .attachments(&iview)
+                .width(self.extent.width)
+                .height(self.extent.height)
+                .layers(1);
+            let framebuffer = unsafe { logical_device.create_framebuffer(&framebuffer_info, None) }?;
+            self.framebuffers.push(framebuffer);
+        }
+
+        Ok(())
+    }
+
+    pub unsafe fn cleanup(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        for fb in &self.framebuffers {
+            logical_device.destroy_framebuffer(*fb, None);
+        }
+        for image in &mut self.images {
+            image.destroy(logical_device, allocator);
+        }
+        for depth_image in &mut self.depth_images {
+            depth_image.destroy(logical_device, allocator);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use ash::vk;
+
+use super::pipeline::{BlendMode, Pipeline, PipelineBuilder};
+use super::descriptor::{layout_binding, DescriptorAllocator, DescriptorLayoutCache, DescriptorWriter};
+use super::error::ReverieError;
+
+use crate::utils::any_as_u8_slice;
+
+/// The scene is drawn into images of this format when the surface needs an output pass.
+/// It keeps values above 1.0 for HDR displays.
+pub const SCENE_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+
+/// How the linear scene colors have to be encoded for the surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputEncoding {
+    /// The surface format is an `_SRGB` format, the hardware encodes while drawing.
+    Srgb = 0,
+    /// An sRGB display behind a `_UNORM` format, encoded by the output pass.
+    SrgbShader = 1,
+    /// Linear Rec. 709 with 1.0 at 80 nits, in a floating point format.
+    ScRgb = 2,
+    /// Rec. 2020 primaries with the PQ transfer function.
+    Hdr10 = 3,
+}
+
+impl OutputEncoding {
+    pub fn for_surface_format(surface_format: vk::SurfaceFormatKHR) -> Self {
+        match surface_format.color_space {
+            vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => OutputEncoding::ScRgb,
+            vk::ColorSpaceKHR::HDR10_ST2084_EXT => OutputEncoding::Hdr10,
+            _ if is_srgb_format(surface_format.format) => OutputEncoding::Srgb,
+            _ => OutputEncoding::SrgbShader
+        }
+    }
+
+    /// Everything but hardware sRGB goes through the scene image and the output pass.
+    pub fn needs_output_pass(&self) -> bool {
+        *self != OutputEncoding::Srgb
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, OutputEncoding::ScRgb | OutputEncoding::Hdr10)
+    }
+}
+
+pub fn is_srgb_format(format: vk::Format) -> bool {
+    matches!(format,
+        vk::Format::R8G8B8A8_SRGB | vk::Format::B8G8R8A8_SRGB | vk::Format::A8B8G8R8_SRGB_PACK32
+            | vk::Format::R8G8B8_SRGB | vk::Format::B8G8R8_SRGB)
+}
+
+/// Brightness of the display for HDR output, ignored for sRGB.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HdrSettings {
+    /// How bright 1.0 in the scene, like a white UI element, appears.
+    pub paper_white_nits: f32,
+    /// Brighter scene values are rolled off towards this peak instead of clipping.
+    pub max_nits: f32,
+}
+
+impl Default for HdrSettings {
+    fn default() -> Self {
+        Self {
+            paper_white_nits: 200.0,
+            max_nits: 1000.0
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct OutputPushConstants {
+    _encoding: u32,
+    _paper_white_nits: f32,
+    _max_nits: f32,
+}
+
+impl OutputPushConstants {
+    pub fn new(encoding: OutputEncoding, hdr: &HdrSettings) -> Self {
+        Self {
+            _encoding: encoding as u32,
+            _paper_white_nits: hdr.paper_white_nits,
+            _max_nits: hdr.max_nits
+        }
+    }
+}
+
+/// Draws the scene image into the swapchain image with a fullscreen triangle, tonemapping and
+/// encoding it for the surface on the way.
+pub struct OutputPass {
+    pub renderpass: vk::RenderPass,
+    pub pipeline: Pipeline,
+    pub encoding: OutputEncoding,
+    pub surface_format: vk::SurfaceFormatKHR,
+    sampler: vk::Sampler,
+    set_layout: vk::DescriptorSetLayout,
+    descriptors: DescriptorAllocator,
+    /// One per scene image, see `update_descriptor_sets`.
+    pub descriptor_sets: Vec<vk::DescriptorSet>,
+}
+
+impl OutputPass {
+    pub fn new(
+        logical_device: &ash::Device,
+        surface_format: vk::SurfaceFormatKHR,
+        layout_cache: &mut DescriptorLayoutCache,
+        pipeline_cache: vk::PipelineCache,
+    ) -> Result<Self, ReverieError> {
+        let set_layout = layout_cache.get_or_create(logical_device, &[
+            layout_binding(0, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::ShaderStageFlags::FRAGMENT)
+        ])?;
+
+        let renderpass = Self::create_render_pass(logical_device, surface_format.format)?;
+
+        let pipeline = PipelineBuilder::new()
+            .shaders(
+                vk_shader_macros::include_glsl!("./shaders/output.vert", kind: vert),
+                vk_shader_macros::include_glsl!("./shaders/output.frag", kind: frag)
+            )
+            .cull_mode(vk::CullModeFlags::NONE)
+            .blend_mode(BlendMode::Opaque)
+            .depth_state(false, false, vk::CompareOp::ALWAYS)
+            .push_constants::<OutputPushConstants>(vk::ShaderStageFlags::FRAGMENT)
+            .descriptor_set_layouts(&[set_layout])
+            // A filled triangle with a line width of 1.0 needs no optional features
+            .build(logical_device, &vk::PhysicalDeviceFeatures::default(), renderpass, layout_cache, pipeline_cache);
+        let pipeline = match pipeline {
+            Ok(pipeline) => pipeline,
+            Err(err) => {
+                unsafe { logical_device.destroy_render_pass(renderpass, None) };
+                return Err(err);
+            }
+        };
+
+        // The scene image matches the swapchain extent, nearest sampling reads it texel for texel
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::NEAREST)
+            .min_filter(vk::Filter::NEAREST)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .max_lod(0.0);
+        let sampler = match unsafe { logical_device.create_sampler(&sampler_info, None) } {
+            Ok(sampler) => sampler,
+            Err(result) => {
+                pipeline.cleanup(logical_device);
+                unsafe { logical_device.destroy_render_pass(renderpass, None) };
+                return Err(result.into());
+            }
+        };
+
+        Ok(Self {
+            renderpass,
+            pipeline,
+            encoding: OutputEncoding::for_surface_format(surface_format),
+            surface_format,
+            sampler,
+            set_layout,
+            descriptors: DescriptorAllocator::new(4),
+            descriptor_sets: vec![]
+        })
+    }
+
+    /// A single color attachment that is fully overwritten and presented afterwards.
+    fn create_render_pass(logical_device: &ash::Device, format: vk::Format) -> Result<vk::RenderPass, ReverieError> {
+        let attachments = [vk::AttachmentDescription::builder()
+            .format(format)
+            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .build()
+        ];
+
+        let color_attachment_references = [vk::AttachmentReference {
+            attachment: 0,
+            layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        }];
+
+        let subpasses = [vk::SubpassDescription::builder()
+            .color_attachments(&color_attachment_references)
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .build()
+        ];
+
+        // Waits for the scene pass to finish writing the scene image, and for the acquired
+        // swapchain image like the scene pass does
+        let subpass_dependencies = [vk::SubpassDependency::builder()
+            .src_subpass(vk::SUBPASS_EXTERNAL)
+            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_subpass(0)
+            .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .dst_access_mask(vk::AccessFlags::SHADER_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .build()
+        ];
+
+        let renderpass_info = vk::RenderPassCreateInfo::builder()
+            .attachments(&attachments)
+            .subpasses(&subpasses)
+            .dependencies(&subpass_dependencies);
+
+        Ok(unsafe { logical_device.create_render_pass(&renderpass_info, None)? })
+    }
+
+    /// Points one descriptor set at each scene image. Call again whenever the images are recreated.
+    pub fn update_descriptor_sets(&mut self, logical_device: &ash::Device, scene_views: &[vk::ImageView]) -> Result<(), ReverieError> {
+        self.descriptors.reset(logical_device)?;
+        self.descriptor_sets.clear();
+
+        for &image_view in scene_views {
+            let set = self.descriptors.allocate(logical_device, self.set_layout)?;
+            DescriptorWriter::new()
+                .write_image(0, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::DescriptorImageInfo {
+                    sampler: self.sampler,
+                    image_view,
+                    image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+                })
+                .update(logical_device, set);
+            self.descriptor_sets.push(set);
+        }
+
+        Ok(())
+    }
+
+    /// Records the pass for the scene image and framebuffer of `image_index`.
+    pub fn record(&self, logical_device: &ash::Device, command_buffer: vk::CommandBuffer, framebuffer: vk::Framebuffer, extent: vk::Extent2D, image_index: usize, hdr: &HdrSettings) {
+        let renderpass_begininfo = vk::RenderPassBeginInfo::builder()
+            .render_pass(self.renderpass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent
+            });
+
+        let push = OutputPushConstants::new(self.encoding, hdr);
+
+        unsafe {
+            logical_device.cmd_begin_render_pass(command_buffer, &renderpass_begininfo, vk::SubpassContents::INLINE);
+
+            logical_device.cmd_set_viewport(command_buffer, 0, &[vk::Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: extent.width as f32,
+                height: extent.height as f32,
+                min_depth: 0.0,
+                max_depth: 1.0,
+            }]);
+            logical_device.cmd_set_scissor(command_buffer, 0, &[vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent
+            }]);
+
+            logical_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, self.pipeline.pipeline);
+            logical_device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline.layout,
+                0,
+                &[self.descriptor_sets[image_index]],
+                &[]
+            );
+            logical_device.cmd_push_constants(
+                command_buffer,
+                self.pipeline.layout,
+                vk::ShaderStageFlags::FRAGMENT,
+                0,
+                any_as_u8_slice(&push)
+            );
+            logical_device.cmd_draw(command_buffer, 3, 1, 0, 0);
+
+            logical_device.cmd_end_render_pass(command_buffer);
+        }
+    }
+
+    /// The set layout belongs to the layout cache and is left alone.
+    pub fn destroy(&mut self, logical_device: &ash::Device) {
+        self.descriptors.destroy(logical_device);
+        self.pipeline.cleanup(logical_device);
+        unsafe {
+            logical_device.destroy_sampler(self.sampler, None);
+            logical_device.destroy_render_pass(self.renderpass, None);
+        }
+    }
+}
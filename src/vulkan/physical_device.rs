@@ -1,10 +1,12 @@
 use ash::vk;
 
+use super::error::ReverieError;
+
 pub struct PhysicalDevice {}
 
 impl PhysicalDevice {
-    pub fn pick_physical_device(instance: &ash::Instance) -> Option<(vk::PhysicalDevice, vk::PhysicalDeviceProperties, vk::PhysicalDeviceFeatures)> {
-        let physical_devices = unsafe { instance.enumerate_physical_devices().expect("Could not enumerate physical devices!") };
+    pub fn pick_physical_device(instance: &ash::Instance) -> Result<(vk::PhysicalDevice, vk::PhysicalDeviceProperties, vk::PhysicalDeviceFeatures), ReverieError> {
+        let physical_devices = unsafe { instance.enumerate_physical_devices()? };
 
         let mut physical_device: vk::PhysicalDevice = vk::PhysicalDevice::null();
         let mut current_score = 0.0;
@@ -17,15 +19,13 @@ impl PhysicalDevice {
             }
         }
 
-        if physical_device == vk::PhysicalDevice::null() { return None; }
+        if physical_device == vk::PhysicalDevice::null() {
+            return Err(ReverieError::DeviceSelection("no device with graphics and transfer queues and geometry shader support".into()));
+        }
         
         let props = unsafe { instance.get_physical_device_properties(physical_device) };
         let features = unsafe { instance.get_physical_device_features(physical_device) };
-        let device_name = String::from(
-            unsafe { std::ffi::CStr::from_ptr(props.device_name.as_ptr()) }
-                .to_str()
-                .unwrap()
-        );
+        let device_name = unsafe { std::ffi::CStr::from_ptr(props.device_name.as_ptr()) }.to_string_lossy();
 
         let driver_major = props.driver_version >> 22;
         let driver_minor = (props.driver_version >> 12) & 0x3ff;
@@ -41,7 +41,31 @@ impl PhysicalDevice {
         println!("[Reverie][info] Device supports Vulkan v{}.{}.{} (variant {}).",
             api_major, api_minor, api_patch, api_variant);
         
-        Some((physical_device, props, features))
+        Ok((physical_device, props, features))
+    }
+
+    /// Picks the most precise depth format the device can use as a depth attachment.
+    pub fn find_depth_format(instance: &ash::Instance, physical_device: vk::PhysicalDevice) -> Option<vk::Format> {
+        let candidates = [
+            vk::Format::D32_SFLOAT,
+            vk::Format::D32_SFLOAT_S8_UINT,
+            vk::Format::D24_UNORM_S8_UINT,
+        ];
+
+        candidates.into_iter().find(|&format| {
+            let props = unsafe { instance.get_physical_device_format_properties(physical_device, format) };
+            props.optimal_tiling_features.contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+        })
+    }
+
+    /// Whether optimally tiled images of `format` can be blitted with linear filtering, which mipmap generation needs.
+    pub fn supports_linear_blit(instance: &ash::Instance, physical_device: vk::PhysicalDevice, format: vk::Format) -> bool {
+        let props = unsafe { instance.get_physical_device_format_properties(physical_device, format) };
+        props.optimal_tiling_features.contains(
+            vk::FormatFeatureFlags::BLIT_SRC
+                | vk::FormatFeatureFlags::BLIT_DST
+                | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR
+        )
     }
 
     pub fn rate_physical_device(instance: &ash::Instance, device: &vk::PhysicalDevice) -> f32 {
@@ -1,27 +1,379 @@
+use std::path::Path;
+
 use ash::vk;
 
-use super::swapchain::VulkanSwapchain;
-use super::vertex::Vertex;
+use super::vertex::{Vertex, Vertex3D};
 
-use crate::PushConstantData;
+use super::renderer::{PushConstantData, PushConstantData3D};
+use super::descriptor::DescriptorLayoutCache;
+use super::reflection::{PipelineInterface, ShaderInterface};
+use super::shader::{builtin_shader_path, create_shader_module, ShaderCompiler, ShaderPaths};
+use super::error::ReverieError;
 
 pub struct Pipeline {
     pub pipeline: vk::Pipeline,
     pub layout: vk::PipelineLayout,
 }
 
-impl Pipeline {
-    pub fn new(logical_device: &ash::Device, swapchain: &VulkanSwapchain, renderpass: &vk::RenderPass) -> Result<Self, vk::Result> {
+/// Refers to one of the renderer's pipelines, either a built-in one or one created through
+/// `VulkanRenderer::create_pipeline`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineHandle(pub(crate) usize);
+
+impl PipelineHandle {
+    /// Default for 2D objects without a texture.
+    pub const BASIC_2D: PipelineHandle = PipelineHandle(0);
+    /// Default for 2D objects with a texture.
+    pub const TEXTURED_2D: PipelineHandle = PipelineHandle(1);
+    /// Default for 3D objects.
+    pub const BASIC_3D: PipelineHandle = PipelineHandle(2);
+}
+
+/// How fragment colors are combined with the color already in the attachment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Overwrites the attachment.
+    Opaque,
+    /// Classic transparency, weighted by the source alpha.
+    Alpha,
+    /// Adds the source color weighted by its alpha, for glows, particles and light.
+    Additive,
+}
+
+impl BlendMode {
+    pub fn attachment_state(&self) -> vk::PipelineColorBlendAttachmentState {
+        let (blend_enable, src_factor, dst_factor) = match self {
+            BlendMode::Opaque => (false, vk::BlendFactor::ONE, vk::BlendFactor::ZERO),
+            BlendMode::Alpha => (true, vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE_MINUS_SRC_ALPHA),
+            BlendMode::Additive => (true, vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE)
+        };
+
+        vk::PipelineColorBlendAttachmentState::builder()
+            .blend_enable(blend_enable)
+            .src_color_blend_factor(src_factor)
+            .dst_color_blend_factor(dst_factor)
+            .color_blend_op(vk::BlendOp::ADD)
+            .src_alpha_blend_factor(src_factor)
+            .dst_alpha_blend_factor(dst_factor)
+            .alpha_blend_op(vk::BlendOp::ADD)
+            .color_write_mask(vk::ColorComponentFlags::RGBA)
+            .build()
+    }
+}
+
+/// Describes a graphics pipeline for the renderer's render pass.
+///
+/// Every setter has a default matching the 2D pipeline: triangle lists, filled polygons,
+/// back-face culling with counter-clockwise front faces, alpha blending and depth testing
+/// with `LESS_OR_EQUAL`. Viewport and scissor are always dynamic, so pipelines do not depend
+/// on the size of the render target.
+///
+/// The vertex layout, push constant ranges and descriptor set layouts are derived from the
+/// shaders through SPIR-V reflection unless they are set explicitly, in which case `build`
+/// checks them against what the shaders declare. Pipelines drawing the renderer's objects should
+/// start from one of the renderer's builders, which declare what the renderer pushes and binds.
+///
+/// The builder owns everything it needs, the renderer keeps it around to rebuild its pipelines
+/// whenever the render pass is recreated or, with hot reload, one of its shader files changes.
+#[derive(Clone, Debug)]
+pub struct PipelineBuilder {
+    vertex_shader: Vec<u32>,
+    fragment_shader: Vec<u32>,
+    shader_paths: Option<ShaderPaths>,
+    vertex_layout: Option<(Vec<vk::VertexInputBindingDescription>, Vec<vk::VertexInputAttributeDescription>)>,
+    topology: vk::PrimitiveTopology,
+    polygon_mode: vk::PolygonMode,
+    cull_mode: vk::CullModeFlags,
+    front_face: vk::FrontFace,
+    line_width: f32,
+    blend_attachment: vk::PipelineColorBlendAttachmentState,
+    depth_test: bool,
+    depth_write: bool,
+    depth_compare_op: vk::CompareOp,
+    push_constant_ranges: Option<Vec<vk::PushConstantRange>>,
+    set_layouts: Option<Vec<vk::DescriptorSetLayout>>,
+}
+
+impl Default for PipelineBuilder {
+    fn default() -> Self {
+        Self {
+            vertex_shader: vec![],
+            fragment_shader: vec![],
+            shader_paths: None,
+            vertex_layout: None,
+            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            polygon_mode: vk::PolygonMode::FILL,
+            cull_mode: vk::CullModeFlags::BACK,
+            front_face: vk::FrontFace::COUNTER_CLOCKWISE,
+            line_width: 1.0,
+            blend_attachment: BlendMode::Alpha.attachment_state(),
+            depth_test: true,
+            depth_write: true,
+            depth_compare_op: vk::CompareOp::LESS_OR_EQUAL, // Equal depths keep submission order
+            push_constant_ranges: None,
+            set_layouts: None
+        }
+    }
+}
+
+impl PipelineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The untextured 2D pipeline: `Vertex` input, `PushConstantData` and the object color.
+    /// Set 0 has to hold the global uniforms.
+    ///
+    /// The built-in presets start with the shaders compiled into the engine, hot reload picks
+    /// up changes to their sources in the engine's `shaders` directory.
+    pub fn basic_2d() -> Self {
+        Self::new()
+            .shaders(
+                vk_shader_macros::include_glsl!("./shaders/basic.vert", kind: vert),
+                vk_shader_macros::include_glsl!("./shaders/basic.frag", kind: frag)
+            )
+            .source_paths(ShaderPaths::new(builtin_shader_path("basic.vert"), builtin_shader_path("basic.frag")))
+            .vertex_layout(&Vertex::get_binding_description(), &Vertex::get_attribute_descriptions())
+            .push_constants::<PushConstantData>(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+    }
+
+    /// Like `basic_2d`, but samples the texture bound to set 1, tinted by the object color.
+    pub fn textured_2d() -> Self {
+        Self::basic_2d()
+            .shaders(
+                vk_shader_macros::include_glsl!("./shaders/textured.vert", kind: vert),
+                vk_shader_macros::include_glsl!("./shaders/textured.frag", kind: frag)
+            )
+            .source_paths(ShaderPaths::new(builtin_shader_path("textured.vert"), builtin_shader_path("textured.frag")))
+    }
+
+    /// The lit 3D pipeline: `Vertex3D` input and `PushConstantData3D`. Set 0 has to hold the global uniforms.
+    pub fn basic_3d() -> Self {
+        Self::new()
+            .shaders(
+                vk_shader_macros::include_glsl!("./shaders/basic3d.vert", kind: vert),
+                vk_shader_macros::include_glsl!("./shaders/basic3d.frag", kind: frag)
+            )
+            .source_paths(ShaderPaths::new(builtin_shader_path("basic3d.vert"), builtin_shader_path("basic3d.frag")))
+            .vertex_layout(&Vertex3D::get_binding_description(), &Vertex3D::get_attribute_descriptions())
+            .push_constants::<PushConstantData3D>(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+    }
+
+    /// SPIR-V code of the vertex and fragment stage, both with a `main` entry point.
+    /// Forgets about any shader files set before, so the shaders are no longer hot reloaded.
+    pub fn shaders(mut self, vertex_shader: &[u32], fragment_shader: &[u32]) -> Self {
+        self.vertex_shader = vertex_shader.to_vec();
+        self.fragment_shader = fragment_shader.to_vec();
+        self.shader_paths = None;
+        self
+    }
+
+    /// Loads both stages from disk, as GLSL (`.vert`, `.frag`) or precompiled SPIR-V (`.spv`).
+    /// The paths are remembered for `reload_shaders` and hot reload.
+    pub fn shader_files<P: AsRef<Path>, Q: AsRef<Path>>(mut self, compiler: &ShaderCompiler, vertex_path: P, fragment_path: Q) -> Result<Self, ReverieError> {
+        let paths = ShaderPaths::new(vertex_path, fragment_path);
+        let (vertex_shader, fragment_shader) = paths.load(compiler)?;
+        self.vertex_shader = vertex_shader;
+        self.fragment_shader = fragment_shader;
+        self.shader_paths = Some(paths);
+        Ok(self)
+    }
+
+    /// Remembers where the current shader code came from without loading it.
+    fn source_paths(mut self, paths: ShaderPaths) -> Self {
+        self.shader_paths = Some(paths);
+        self
+    }
+
+    pub fn shader_paths(&self) -> Option<&ShaderPaths> {
+        self.shader_paths.as_ref()
+    }
+
+    /// Loads the shader files again. On failure the previous code is kept.
+    /// Does nothing for builders whose shaders were not loaded from files.
+    pub fn reload_shaders(&mut self, compiler: &ShaderCompiler) -> Result<(), ReverieError> {
+        if let Some(paths) = &self.shader_paths {
+            let (vertex_shader, fragment_shader) = paths.load(compiler)?;
+            self.vertex_shader = vertex_shader;
+            self.fragment_shader = fragment_shader;
+        }
+        Ok(())
+    }
+
+    /// Without a vertex layout, the shader inputs are read from a single, tightly packed binding in location order.
+    pub fn vertex_layout(mut self, bindings: &[vk::VertexInputBindingDescription], attributes: &[vk::VertexInputAttributeDescription]) -> Self {
+        self.vertex_layout = Some((bindings.to_vec(), attributes.to_vec()));
+        self
+    }
+
+    /// Point topologies need the vertex shader to write `gl_PointSize`.
+    pub fn topology(mut self, topology: vk::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// `LINE` and `POINT` need the `fillModeNonSolid` device feature, which the renderer enables when available.
+    /// `build` fails without it.
+    pub fn polygon_mode(mut self, polygon_mode: vk::PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    pub fn cull_mode(mut self, cull_mode: vk::CullModeFlags) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    /// Defaults to `COUNTER_CLOCKWISE`, which matches the y-up world space of `Camera`. Geometry
+    /// wound for y-down coordinates, like clip space, needs `CLOCKWISE`.
+    pub fn front_face(mut self, front_face: vk::FrontFace) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    /// Widths other than 1.0 need the `wideLines` device feature, which the renderer enables when available.
+    /// `build` fails without it.
+    pub fn line_width(mut self, line_width: f32) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_attachment = blend_mode.attachment_state();
+        self
+    }
+
+    /// Full control over blending, for anything `BlendMode` does not cover.
+    pub fn blend_attachment(mut self, blend_attachment: vk::PipelineColorBlendAttachmentState) -> Self {
+        self.blend_attachment = blend_attachment;
+        self
+    }
+
+    pub fn depth_state(mut self, depth_test: bool, depth_write: bool, depth_compare_op: vk::CompareOp) -> Self {
+        self.depth_test = depth_test;
+        self.depth_write = depth_write;
+        self.depth_compare_op = depth_compare_op;
+        self
+    }
+
+    /// Replaces all push constant ranges. Without any, a single range covering the push constant
+    /// blocks of both stages is used.
+    pub fn push_constant_ranges(mut self, ranges: &[vk::PushConstantRange]) -> Self {
+        self.push_constant_ranges = Some(ranges.to_vec());
+        self
+    }
+
+    /// Shorthand for a single range covering `T`, starting at offset 0.
+    pub fn push_constants<T>(self, stage_flags: vk::ShaderStageFlags) -> Self {
+        self.push_constant_ranges(&[vk::PushConstantRange {
+            stage_flags,
+            offset: 0,
+            size: std::mem::size_of::<T>() as u32
+        }])
+    }
+
+    /// The layouts of set 0, 1 and so on. Without them, the layouts are derived from the bindings
+    /// the shaders use, visible to exactly the stages using them.
+    ///
+    /// Only layouts that came from the layout cache passed to `build` can be checked against the shaders.
+    pub fn descriptor_set_layouts(mut self, set_layouts: &[vk::DescriptorSetLayout]) -> Self {
+        self.set_layouts = Some(set_layouts.to_vec());
+        self
+    }
+
+    /// Reads what the shaders expect from the pipeline.
+    pub fn reflect(&self) -> Result<PipelineInterface, ReverieError> {
+        if self.vertex_shader.is_empty() || self.fragment_shader.is_empty() {
+            return Err(ReverieError::Shader("pipeline needs both a vertex and a fragment shader".into()));
+        }
+
+        PipelineInterface::new(&ShaderInterface::reflect(&self.vertex_shader)?, &ShaderInterface::reflect(&self.fragment_shader)?)
+    }
+
+    /// Fills in everything not set explicitly from the shaders and checks everything else against them.
+    fn resolve_layout(&self, logical_device: &ash::Device, layout_cache: &mut DescriptorLayoutCache) -> Result<ResolvedLayout, ReverieError> {
+        let interface = self.reflect()?;
+
+        let (vertex_bindings, vertex_attributes) = match &self.vertex_layout {
+            Some((bindings, attributes)) => {
+                interface.validate_vertex_layout(attributes)?;
+                (bindings.clone(), attributes.clone())
+            },
+            None => interface.packed_vertex_layout()?
+        };
+
+        let push_constant_ranges = match &self.push_constant_ranges {
+            Some(ranges) => {
+                interface.validate_push_constants(ranges)?;
+                ranges.clone()
+            },
+            None => interface.push_constant_ranges()
+        };
+
+        let set_layouts = match &self.set_layouts {
+            Some(set_layouts) => {
+                if interface.sets.len() > set_layouts.len() {
+                    return Err(ReverieError::InterfaceMismatch(format!(
+                        "the shaders use set {}, but the pipeline only has {} descriptor set layouts",
+                        interface.sets.len() - 1, set_layouts.len()
+                    )));
+                }
+                for (set, &layout) in set_layouts.iter().enumerate() {
+                    if let Some(bindings) = layout_cache.bindings(layout) {
+                        interface.validate_set_layout(set as u32, &bindings)?;
+                    }
+                }
+                set_layouts.clone()
+            },
+            None => interface.sets
+                .iter()
+                .map(|bindings| layout_cache.get_or_create(logical_device, bindings))
+                .collect::<Result<_, _>>()?
+        };
+
+        Ok(ResolvedLayout {
+            vertex_bindings,
+            vertex_attributes,
+            push_constant_ranges,
+            set_layouts
+        })
+    }
+
+    /// Fails if the polygon mode or line width needs a feature missing from `enabled_features`,
+    /// the features the logical device was created with.
+    pub fn check_features(&self, enabled_features: &vk::PhysicalDeviceFeatures) -> Result<(), ReverieError> {
+        if self.polygon_mode != vk::PolygonMode::FILL && enabled_features.fill_mode_non_solid == vk::FALSE {
+            return Err(ReverieError::UnsupportedFeature(format!(
+                "polygon mode {:?} needs fillModeNonSolid", self.polygon_mode
+            )));
+        }
+        if self.line_width != 1.0 && enabled_features.wide_lines == vk::FALSE {
+            return Err(ReverieError::UnsupportedFeature(format!(
+                "line width {} needs wideLines", self.line_width
+            )));
+        }
+        Ok(())
+    }
+
+    /// Derived descriptor set layouts are created in `layout_cache`, which has to outlive the pipeline.
+    /// `pipeline_cache` may be null. See `check_features` for `enabled_features`.
+    pub fn build(
+        &self,
+        logical_device: &ash::Device,
+        enabled_features: &vk::PhysicalDeviceFeatures,
+        renderpass: vk::RenderPass,
+        layout_cache: &mut DescriptorLayoutCache,
+        pipeline_cache: vk::PipelineCache,
+    ) -> Result<Pipeline, ReverieError> {
+        self.check_features(enabled_features)?;
+        let layout = self.resolve_layout(logical_device, layout_cache)?;
+
         let main_function_name = std::ffi::CString::new("main").unwrap();
 
-        let vertexshader_createinfo = vk::ShaderModuleCreateInfo::builder()
-            .code(vk_shader_macros::include_glsl!("./shaders/basic.vert", kind: vert));
-        let vertexshader_module = unsafe { logical_device.create_shader_module(&vertexshader_createinfo, None)? };
+        let vertexshader_module = create_shader_module(logical_device, &self.vertex_shader, "vertex")?;
+        let fragmentshader_module = create_shader_module(logical_device, &self.fragment_shader, "fragment")
+            .inspect_err(|_| unsafe { logical_device.destroy_shader_module(vertexshader_module, None) })?;
 
-        let fragmentshader_createinfo = vk::ShaderModuleCreateInfo::builder()
-            .code(vk_shader_macros::include_glsl!("./shaders/basic.frag", kind: frag));
-        let fragmentshader_module = unsafe { logical_device.create_shader_module(&fragmentshader_createinfo, None)? };
-        
         let vertexshader_stage = vk::PipelineShaderStageCreateInfo::builder()
             .stage(vk::ShaderStageFlags::VERTEX)
             .module(vertexshader_module)
@@ -30,86 +382,58 @@ impl Pipeline {
             .stage(vk::ShaderStageFlags::FRAGMENT)
             .module(fragmentshader_module)
             .name(&main_function_name);
-        
-        let shader_stages = [vertexshader_stage.build(), fragmentshader_stage.build()];
 
-        let vertex_attribute_descscriptions = Vertex::get_attribute_descriptions();
-        let vertex_binding_descriptions = Vertex::get_binding_description();
+        let shader_stages = [vertexshader_stage.build(), fragmentshader_stage.build()];
 
         let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::builder()
-            .vertex_attribute_descriptions(&vertex_attribute_descscriptions)
-            .vertex_binding_descriptions(&vertex_binding_descriptions);
+            .vertex_attribute_descriptions(&layout.vertex_attributes)
+            .vertex_binding_descriptions(&layout.vertex_bindings);
 
         let input_assembly_info = vk::PipelineInputAssemblyStateCreateInfo::builder()
-            .topology(vk::PrimitiveTopology::TRIANGLE_LIST);
-
-        let viewports = [vk::Viewport {
-            x: 0.0,
-            y: 0.0,
-            width: swapchain.extent.width as f32,
-            height: swapchain.extent.height as f32,
-            min_depth: 0.0,
-            max_depth: 1.0,
-        }];
-
-        let scissors = [vk::Rect2D {
-            offset: vk::Offset2D { x: 0, y: 0 },
-            extent: swapchain.extent
-        }];
-        
+            .topology(self.topology);
+
+        // Both are dynamic, only the counts matter here
         let viewport_info = vk::PipelineViewportStateCreateInfo::builder()
-            .viewports(&viewports)
-            .scissors(&scissors);
+            .viewport_count(1)
+            .scissor_count(1);
 
         let rasterizer_info = vk::PipelineRasterizationStateCreateInfo::builder()
-            .line_width(1.0)
+            .line_width(self.line_width)
             .depth_clamp_enable(false)
-            .front_face(vk::FrontFace::CLOCKWISE)
-            .cull_mode(vk::CullModeFlags::BACK)
-            .polygon_mode(vk::PolygonMode::FILL);
+            .front_face(self.front_face)
+            .cull_mode(self.cull_mode)
+            .polygon_mode(self.polygon_mode);
 
         let multisampler_info = vk::PipelineMultisampleStateCreateInfo::builder()
             .rasterization_samples(vk::SampleCountFlags::TYPE_1);
 
-        let colorblend_attachments = [vk::PipelineColorBlendAttachmentState::builder()
-            .blend_enable(true)
-            .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
-            .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
-            .color_blend_op(vk::BlendOp::ADD)
-            .src_alpha_blend_factor(vk::BlendFactor::SRC_ALPHA)
-            .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
-            .alpha_blend_op(vk::BlendOp::ADD)
-            .color_write_mask(
-                vk::ColorComponentFlags::R
-                    | vk::ColorComponentFlags::G
-                    | vk::ColorComponentFlags::B
-                    | vk::ColorComponentFlags::A
-            )
-            .build()
-        ];
-        
+        let colorblend_attachments = [self.blend_attachment];
+
         let colorblend_info = vk::PipelineColorBlendStateCreateInfo::builder().attachments(&colorblend_attachments);
 
         let depthstencil_info = vk::PipelineDepthStencilStateCreateInfo::builder()
-            .depth_test_enable(true)
-            .depth_write_enable(true)
-            .depth_compare_op(vk::CompareOp::LESS)
+            .depth_test_enable(self.depth_test)
+            .depth_write_enable(self.depth_write)
+            .depth_compare_op(self.depth_compare_op)
             .depth_bounds_test_enable(false)
             .stencil_test_enable(false);
-        
+
         let dynamic_state_info = vk::PipelineDynamicStateCreateInfo::builder()
             .dynamic_states(&[vk::DynamicState::SCISSOR, vk::DynamicState::VIEWPORT]);
 
-        let push_constant_range = [vk::PushConstantRange::builder()
-            .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
-            .offset(0)
-            .size(std::mem::size_of::<PushConstantData>() as u32)
-            .build()
-        ];
-
         let pipelinelayout_info = vk::PipelineLayoutCreateInfo::builder()
-            .push_constant_ranges(&push_constant_range);
-        let pipeline_layout = unsafe { logical_device.create_pipeline_layout(&pipelinelayout_info, None)? };
+            .set_layouts(&layout.set_layouts)
+            .push_constant_ranges(&layout.push_constant_ranges);
+        let pipeline_layout = match unsafe { logical_device.create_pipeline_layout(&pipelinelayout_info, None) } {
+            Ok(pipeline_layout) => pipeline_layout,
+            Err(result) => {
+                unsafe {
+                    logical_device.destroy_shader_module(fragmentshader_module, None);
+                    logical_device.destroy_shader_module(vertexshader_module, None);
+                }
+                return Err(result.into());
+            }
+        };
 
         let pipeline_info = vk::GraphicsPipelineCreateInfo::builder()
             .stages(&shader_stages)
@@ -122,29 +446,47 @@ impl Pipeline {
             .depth_stencil_state(&depthstencil_info)
             .dynamic_state(&dynamic_state_info)
             .layout(pipeline_layout)
-            .render_pass(*renderpass)
+            .render_pass(renderpass)
             .subpass(0);
 
-        let graphics_pipeline = unsafe {
-            logical_device.create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_info.build()], None)
-                .expect("Failed to create graphics pipeline")
-        }[0];
+        let graphics_pipelines = unsafe {
+            logical_device.create_graphics_pipelines(pipeline_cache, &[pipeline_info.build()], None)
+        };
 
+        // The modules are only needed during pipeline creation, whether it succeeded or not
         unsafe {
             logical_device.destroy_shader_module(fragmentshader_module, None);
             logical_device.destroy_shader_module(vertexshader_module, None);
         }
 
-        Ok(Self {
+        let graphics_pipeline = match graphics_pipelines {
+            Ok(pipelines) => pipelines[0],
+            Err((_, result)) => {
+                unsafe { logical_device.destroy_pipeline_layout(pipeline_layout, None) };
+                return Err(ReverieError::from_vk_or(result, |result| ReverieError::Shader(format!("failed to create graphics pipeline ({})", result))));
+            }
+        };
+
+        Ok(Pipeline {
             pipeline: graphics_pipeline,
             layout: pipeline_layout
         })
     }
+}
+
+/// The layout parts of a `PipelineBuilder` after reflection.
+struct ResolvedLayout {
+    vertex_bindings: Vec<vk::VertexInputBindingDescription>,
+    vertex_attributes: Vec<vk::VertexInputAttributeDescription>,
+    push_constant_ranges: Vec<vk::PushConstantRange>,
+    set_layouts: Vec<vk::DescriptorSetLayout>,
+}
 
+impl Pipeline {
     pub fn cleanup(&self, logical_device: &ash::Device) {
         unsafe {
             logical_device.destroy_pipeline(self.pipeline, None);
             logical_device.destroy_pipeline_layout(self.layout, None);
         }
     }
-}
\ No newline at end of file
+}
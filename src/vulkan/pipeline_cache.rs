@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+
+use ash::vk;
+
+use super::error::ReverieError;
+
+/// Marks files written by `PipelineCache::save`.
+const FILE_MAGIC: [u8; 4] = *b"RVPC";
+/// Bumped whenever the layout of the file header changes.
+const FILE_VERSION: u32 = 1;
+/// Magic, version, driver version, data length and checksum.
+const FILE_HEADER_SIZE: usize = 4 + 4 + 4 + 8 + 8;
+/// Size of the header every Vulkan pipeline cache starts with, for `VK_PIPELINE_CACHE_HEADER_VERSION_ONE`.
+const VK_HEADER_SIZE: usize = 16 + vk::UUID_SIZE;
+
+/// A `vk::PipelineCache` that is loaded from and saved to disk, so pipelines compiled in one run
+/// are reused by the next one.
+///
+/// Each device and driver version gets its own file. The contents are checked before they reach
+/// the driver: a file from another device or driver, a truncated or otherwise corrupt file, or
+/// one the driver refuses is ignored and replaced on the next save.
+pub struct PipelineCache {
+    pub cache: vk::PipelineCache,
+    path: Option<PathBuf>,
+}
+
+impl PipelineCache {
+    /// Loads the cache for the device from `dir`, or starts empty. Without a directory the cache
+    /// only lives in memory.
+    pub fn new(logical_device: &ash::Device, properties: &vk::PhysicalDeviceProperties, dir: Option<&Path>) -> Result<Self, ReverieError> {
+        let path = dir.map(|dir| dir.join(file_name(properties)));
+
+        let initial_data = match &path {
+            Some(path) => match std::fs::read(path) {
+                Ok(file) => match unwrap_file(&file, properties) {
+                    Some(data) => {
+                        println!("[Reverie][info] Loaded pipeline cache from {} ({} bytes)", path.display(), data.len());
+                        data.to_vec()
+                    },
+                    None => {
+                        println!("[Reverie][warn] Ignoring stale or corrupt pipeline cache {}", path.display());
+                        vec![]
+                    }
+                },
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+                Err(err) => {
+                    println!("[Reverie][warn] Failed to read pipeline cache {}: {}", path.display(), err);
+                    vec![]
+                }
+            },
+            None => vec![]
+        };
+
+        let create_info = vk::PipelineCacheCreateInfo::builder().initial_data(&initial_data);
+        let cache = match unsafe { logical_device.create_pipeline_cache(&create_info, None) } {
+            Ok(cache) => cache,
+            // Drivers may still reject data that passed the checks, start over without it
+            Err(result) if !initial_data.is_empty() && !ReverieError::from(result).is_out_of_memory() => {
+                println!("[Reverie][warn] The driver rejected the pipeline cache ({}), starting with an empty one", result);
+                unsafe { logical_device.create_pipeline_cache(&vk::PipelineCacheCreateInfo::default(), None)? }
+            },
+            Err(result) => return Err(result.into())
+        };
+
+        Ok(Self {
+            cache,
+            path
+        })
+    }
+
+    /// Where the cache is saved, if anywhere.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Writes the cache to disk. The file is replaced in one step, so an interrupted save never
+    /// leaves a half written cache behind.
+    pub fn save(&self, logical_device: &ash::Device, properties: &vk::PhysicalDeviceProperties) -> Result<(), ReverieError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(())
+        };
+
+        let data = unsafe { logical_device.get_pipeline_cache_data(self.cache)? };
+        let file = wrap_data(&data, properties);
+
+        let io_error = |err: std::io::Error| ReverieError::PipelineCache(format!("failed to write {}: {}", path.display(), err));
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        // Unique per process, so renderers in different processes never write the same temporary file
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&temp_path, file).map_err(io_error)?;
+        std::fs::rename(&temp_path, path).map_err(io_error)?;
+
+        Ok(())
+    }
+
+    pub fn destroy(&mut self, logical_device: &ash::Device) {
+        unsafe { logical_device.destroy_pipeline_cache(self.cache, None) };
+        self.cache = vk::PipelineCache::null();
+    }
+}
+
+/// The per user cache directory of the platform, falling back to the temporary directory.
+pub fn default_cache_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| {
+            let home = PathBuf::from(home);
+            if cfg!(target_os = "macos") { home.join("Library/Caches") } else { home.join(".cache") }
+        }))
+        .unwrap_or_else(std::env::temp_dir);
+    base.join("reverie")
+}
+
+/// Names the file after the device's pipeline cache UUID and the driver version.
+pub fn file_name(properties: &vk::PhysicalDeviceProperties) -> String {
+    let uuid: String = properties.pipeline_cache_uuid.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("pipelines-{:04x}-{:04x}-{}-{:08x}.bin", properties.vendor_id, properties.device_id, uuid, properties.driver_version)
+}
+
+/// FNV-1a, enough to notice truncated or damaged files.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Prepends the file header to the data returned by `vkGetPipelineCacheData`.
+pub fn wrap_data(data: &[u8], properties: &vk::PhysicalDeviceProperties) -> Vec<u8> {
+    let mut file = Vec::with_capacity(FILE_HEADER_SIZE + data.len());
+    file.extend_from_slice(&FILE_MAGIC);
+    file.extend_from_slice(&FILE_VERSION.to_le_bytes());
+    file.extend_from_slice(&properties.driver_version.to_le_bytes());
+    file.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    file.extend_from_slice(&checksum(data).to_le_bytes());
+    file.extend_from_slice(data);
+    file
+}
+
+/// Returns the pipeline cache data in `file` if it was saved by this version of the engine for
+/// this device and driver and is intact, `None` otherwise.
+pub fn unwrap_file<'a>(file: &'a [u8], properties: &vk::PhysicalDeviceProperties) -> Option<&'a [u8]> {
+    if file.len() < FILE_HEADER_SIZE || file[0..4] != FILE_MAGIC {
+        return None;
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(file[offset..offset + 4].try_into().unwrap());
+    let read_u64 = |offset: usize| u64::from_le_bytes(file[offset..offset + 8].try_into().unwrap());
+
+    if read_u32(4) != FILE_VERSION || read_u32(8) != properties.driver_version {
+        return None;
+    }
+
+    let data = &file[FILE_HEADER_SIZE..];
+    if read_u64(12) != data.len() as u64 || read_u64(20) != checksum(data) {
+        return None;
+    }
+
+    is_compatible(data, properties).then_some(data)
+}
+
+/// Checks the header Vulkan puts in front of the cache data against the device.
+pub fn is_compatible(data: &[u8], properties: &vk::PhysicalDeviceProperties) -> bool {
+    if data.len() < VK_HEADER_SIZE {
+        return false;
+    }
+
+    // Unlike the file header, the Vulkan header is in host byte order
+    let read_u32 = |offset: usize| u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap());
+
+    let header_size = read_u32(0);
+    let header_version = read_u32(4);
+    header_size as usize >= VK_HEADER_SIZE
+        && header_size as usize <= data.len()
+        && header_version == vk::PipelineCacheHeaderVersion::ONE.as_raw() as u32
+        && read_u32(8) == properties.vendor_id
+        && read_u32(12) == properties.device_id
+        && data[16..VK_HEADER_SIZE] == properties.pipeline_cache_uuid
+}
@@ -1,5 +1,6 @@
 use ash::vk;
 use super::surface::VulkanSurface;
+use super::error::ReverieError;
 
 pub struct QueueFamilies {
     pub graphics: Option<u32>,
@@ -7,7 +8,7 @@ pub struct QueueFamilies {
 }
 
 impl QueueFamilies {
-    pub fn new(instance: &ash::Instance, physical_device: vk::PhysicalDevice, surface: &VulkanSurface) -> Result<QueueFamilies, vk::Result> {
+    pub fn new(instance: &ash::Instance, physical_device: vk::PhysicalDevice, surface: Option<&VulkanSurface>) -> Result<QueueFamilies, ReverieError> {
         let mut queue_families = QueueFamilies {
             graphics: None,
             transfer: None,
@@ -18,10 +19,13 @@ impl QueueFamilies {
         let mut found_transfer_queue_index = None;
         
         for (index, queue_family) in queue_family_properties.iter().enumerate() {
-            if queue_family.queue_count > 0 && queue_family.queue_flags.contains(vk::QueueFlags::GRAPHICS) &&
-                unsafe { surface.surface_loader.get_physical_device_surface_support(physical_device, index as u32, surface.surface).unwrap() } {
-                    found_graphics_queue_index = Some(index as u32);
-                }
+            let can_present = match surface {
+                Some(surface) => surface.get_physical_device_surface_support(physical_device, index)?,
+                None => true
+            };
+            if queue_family.queue_count > 0 && queue_family.queue_flags.contains(vk::QueueFlags::GRAPHICS) && can_present {
+                found_graphics_queue_index = Some(index as u32);
+            }
             if queue_family.queue_count > 0 && queue_family.queue_flags.contains(vk::QueueFlags::TRANSFER) {
                 if found_transfer_queue_index.is_none() || !queue_family.queue_flags.contains(vk::QueueFlags::GRAPHICS) {
                     found_transfer_queue_index = Some(index as u32);
@@ -29,6 +33,14 @@ impl QueueFamilies {
             }
         }
 
+        if found_graphics_queue_index.is_none() {
+            return Err(ReverieError::DeviceSelection("no graphics queue family that can present to the surface".into()));
+        }
+        if found_transfer_queue_index.is_none() {
+            return Err(ReverieError::DeviceSelection("no transfer queue family".into()));
+        }
+
+        // Both are guaranteed to be set from here on
         queue_families.graphics = found_graphics_queue_index;
         queue_families.transfer = found_transfer_queue_index;
 
@@ -0,0 +1,350 @@
+use ash::vk;
+use spirv_reflect::types::{ReflectDecorationFlags, ReflectDescriptorType, ReflectFormat, ReflectShaderStageFlags};
+
+use super::error::ReverieError;
+
+/// A vertex shader input, as declared in the shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReflectedInput {
+    pub location: u32,
+    pub name: String,
+    /// The matching 32 bit format, e.g. `R32G32_SFLOAT` for a `vec2`.
+    pub format: vk::Format,
+}
+
+/// A descriptor binding used by a shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReflectedBinding {
+    pub set: u32,
+    pub binding: u32,
+    pub name: String,
+    pub descriptor_type: vk::DescriptorType,
+    pub descriptor_count: u32,
+}
+
+/// The interface of a single shader stage, read from its SPIR-V.
+#[derive(Clone, Debug)]
+pub struct ShaderInterface {
+    pub stage: vk::ShaderStageFlags,
+    /// Inputs with a location, built-ins like `gl_VertexIndex` are left out.
+    pub inputs: Vec<ReflectedInput>,
+    /// The bytes of the push constant block, if the stage declares one.
+    pub push_constants: Option<vk::PushConstantRange>,
+    pub bindings: Vec<ReflectedBinding>,
+}
+
+impl ShaderInterface {
+    pub fn reflect(code: &[u32]) -> Result<Self, ReverieError> {
+        let module = spirv_reflect::ShaderModule::load_u32_data(code)
+            .map_err(|err| ReverieError::Shader(format!("failed to reflect shader: {}", err)))?;
+        let reflect_error = |err: &str| ReverieError::Shader(format!("failed to reflect shader: {}", err));
+
+        let stage = stage_flags(module.get_shader_stage())?;
+
+        let mut inputs: Vec<ReflectedInput> = module.enumerate_input_variables(None)
+            .map_err(reflect_error)?
+            .into_iter()
+            .filter(|input| !input.decoration_flags.contains(ReflectDecorationFlags::BUILT_IN))
+            .map(|input| ReflectedInput {
+                location: input.location,
+                format: vertex_format(input.format),
+                name: input.name
+            })
+            .collect();
+        inputs.sort_by_key(|input| input.location);
+
+        // GLSL allows a single push constant block per stage
+        let push_constants = module.enumerate_push_constant_blocks(None)
+            .map_err(reflect_error)?
+            .first()
+            .map(|block| vk::PushConstantRange {
+                stage_flags: stage,
+                offset: block.offset,
+                size: block.size
+            });
+
+        let mut bindings = vec![];
+        for binding in module.enumerate_descriptor_bindings(None).map_err(reflect_error)? {
+            bindings.push(ReflectedBinding {
+                set: binding.set,
+                binding: binding.binding,
+                descriptor_type: descriptor_type(binding.descriptor_type)
+                    .ok_or_else(|| ReverieError::Shader(format!("{} uses an unsupported descriptor type {:?}", binding.name, binding.descriptor_type)))?,
+                descriptor_count: binding.count.max(1),
+                name: binding.name
+            });
+        }
+        bindings.sort_by_key(|binding| (binding.set, binding.binding));
+
+        Ok(Self {
+            stage,
+            inputs,
+            push_constants,
+            bindings
+        })
+    }
+}
+
+fn stage_flags(stage: ReflectShaderStageFlags) -> Result<vk::ShaderStageFlags, ReverieError> {
+    match stage {
+        ReflectShaderStageFlags::VERTEX => Ok(vk::ShaderStageFlags::VERTEX),
+        ReflectShaderStageFlags::FRAGMENT => Ok(vk::ShaderStageFlags::FRAGMENT),
+        stage => Err(ReverieError::Shader(format!("unsupported shader stage {:?}, only vertex and fragment shaders can be used", stage)))
+    }
+}
+
+fn vertex_format(format: ReflectFormat) -> vk::Format {
+    match format {
+        ReflectFormat::Undefined => vk::Format::UNDEFINED,
+        ReflectFormat::R32_UINT => vk::Format::R32_UINT,
+        ReflectFormat::R32_SINT => vk::Format::R32_SINT,
+        ReflectFormat::R32_SFLOAT => vk::Format::R32_SFLOAT,
+        ReflectFormat::R32G32_UINT => vk::Format::R32G32_UINT,
+        ReflectFormat::R32G32_SINT => vk::Format::R32G32_SINT,
+        ReflectFormat::R32G32_SFLOAT => vk::Format::R32G32_SFLOAT,
+        ReflectFormat::R32G32B32_UINT => vk::Format::R32G32B32_UINT,
+        ReflectFormat::R32G32B32_SINT => vk::Format::R32G32B32_SINT,
+        ReflectFormat::R32G32B32_SFLOAT => vk::Format::R32G32B32_SFLOAT,
+        ReflectFormat::R32G32B32A32_UINT => vk::Format::R32G32B32A32_UINT,
+        ReflectFormat::R32G32B32A32_SINT => vk::Format::R32G32B32A32_SINT,
+        ReflectFormat::R32G32B32A32_SFLOAT => vk::Format::R32G32B32A32_SFLOAT
+    }
+}
+
+fn descriptor_type(descriptor_type: ReflectDescriptorType) -> Option<vk::DescriptorType> {
+    match descriptor_type {
+        ReflectDescriptorType::Sampler => Some(vk::DescriptorType::SAMPLER),
+        ReflectDescriptorType::CombinedImageSampler => Some(vk::DescriptorType::COMBINED_IMAGE_SAMPLER),
+        ReflectDescriptorType::SampledImage => Some(vk::DescriptorType::SAMPLED_IMAGE),
+        ReflectDescriptorType::StorageImage => Some(vk::DescriptorType::STORAGE_IMAGE),
+        ReflectDescriptorType::UniformTexelBuffer => Some(vk::DescriptorType::UNIFORM_TEXEL_BUFFER),
+        ReflectDescriptorType::StorageTexelBuffer => Some(vk::DescriptorType::STORAGE_TEXEL_BUFFER),
+        ReflectDescriptorType::UniformBuffer => Some(vk::DescriptorType::UNIFORM_BUFFER),
+        ReflectDescriptorType::StorageBuffer => Some(vk::DescriptorType::STORAGE_BUFFER),
+        ReflectDescriptorType::UniformBufferDynamic => Some(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC),
+        ReflectDescriptorType::StorageBufferDynamic => Some(vk::DescriptorType::STORAGE_BUFFER_DYNAMIC),
+        ReflectDescriptorType::InputAttachment => Some(vk::DescriptorType::INPUT_ATTACHMENT),
+        ReflectDescriptorType::Undefined | ReflectDescriptorType::AccelerationStructureNV => None
+    }
+}
+
+/// How many components a vertex attribute format has, and whether they are read as floats,
+/// signed or unsigned integers. `None` for formats the validation does not know about.
+fn format_class(format: vk::Format) -> Option<(u32, char)> {
+    let class = match format {
+        vk::Format::R32_SFLOAT | vk::Format::R16_SFLOAT | vk::Format::R8_UNORM | vk::Format::R8_SNORM
+            | vk::Format::R16_UNORM | vk::Format::R16_SNORM => (1, 'f'),
+        vk::Format::R32G32_SFLOAT | vk::Format::R16G16_SFLOAT | vk::Format::R8G8_UNORM | vk::Format::R8G8_SNORM
+            | vk::Format::R16G16_UNORM | vk::Format::R16G16_SNORM => (2, 'f'),
+        vk::Format::R32G32B32_SFLOAT | vk::Format::R16G16B16_SFLOAT | vk::Format::R8G8B8_UNORM | vk::Format::R8G8B8_SNORM => (3, 'f'),
+        vk::Format::R32G32B32A32_SFLOAT | vk::Format::R16G16B16A16_SFLOAT | vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SNORM
+            | vk::Format::B8G8R8A8_UNORM | vk::Format::R16G16B16A16_UNORM | vk::Format::R16G16B16A16_SNORM
+            | vk::Format::A2B10G10R10_UNORM_PACK32 => (4, 'f'),
+        vk::Format::R32_SINT | vk::Format::R16_SINT | vk::Format::R8_SINT => (1, 'i'),
+        vk::Format::R32G32_SINT | vk::Format::R16G16_SINT | vk::Format::R8G8_SINT => (2, 'i'),
+        vk::Format::R32G32B32_SINT => (3, 'i'),
+        vk::Format::R32G32B32A32_SINT | vk::Format::R16G16B16A16_SINT | vk::Format::R8G8B8A8_SINT => (4, 'i'),
+        vk::Format::R32_UINT | vk::Format::R16_UINT | vk::Format::R8_UINT => (1, 'u'),
+        vk::Format::R32G32_UINT | vk::Format::R16G16_UINT | vk::Format::R8G8_UINT => (2, 'u'),
+        vk::Format::R32G32B32_UINT => (3, 'u'),
+        vk::Format::R32G32B32A32_UINT | vk::Format::R16G16B16A16_UINT | vk::Format::R8G8B8A8_UINT => (4, 'u'),
+        _ => return None
+    };
+    Some(class)
+}
+
+/// Size of the 32 bit formats reflection produces.
+fn format_size(format: vk::Format) -> Option<u32> {
+    match format {
+        vk::Format::R32_SFLOAT | vk::Format::R32_SINT | vk::Format::R32_UINT => Some(4),
+        vk::Format::R32G32_SFLOAT | vk::Format::R32G32_SINT | vk::Format::R32G32_UINT => Some(8),
+        vk::Format::R32G32B32_SFLOAT | vk::Format::R32G32B32_SINT | vk::Format::R32G32B32_UINT => Some(12),
+        vk::Format::R32G32B32A32_SFLOAT | vk::Format::R32G32B32A32_SINT | vk::Format::R32G32B32A32_UINT => Some(16),
+        _ => None
+    }
+}
+
+/// What the vertex and fragment shader of a pipeline expect from it, merged over both stages.
+#[derive(Clone, Debug)]
+pub struct PipelineInterface {
+    pub vertex_inputs: Vec<ReflectedInput>,
+    /// The push constant block of each stage that has one.
+    pub push_constant_blocks: Vec<vk::PushConstantRange>,
+    /// Bindings per set, with the stage flags of every stage using them. Sets the shaders
+    /// skip are empty.
+    pub sets: Vec<Vec<vk::DescriptorSetLayoutBinding>>,
+}
+
+impl PipelineInterface {
+    pub fn new(vertex: &ShaderInterface, fragment: &ShaderInterface) -> Result<Self, ReverieError> {
+        if vertex.stage != vk::ShaderStageFlags::VERTEX {
+            return Err(ReverieError::Shader("the vertex stage is not a vertex shader".into()));
+        }
+        if fragment.stage != vk::ShaderStageFlags::FRAGMENT {
+            return Err(ReverieError::Shader("the fragment stage is not a fragment shader".into()));
+        }
+
+        let push_constant_blocks = [vertex, fragment]
+            .iter()
+            .filter_map(|shader| shader.push_constants)
+            .collect();
+
+        let mut sets: Vec<Vec<vk::DescriptorSetLayoutBinding>> = vec![];
+        for (stage, used) in [vertex, fragment].iter().flat_map(|shader| shader.bindings.iter().map(move |used| (shader.stage, used))) {
+            if sets.len() <= used.set as usize {
+                sets.resize(used.set as usize + 1, vec![]);
+            }
+            let set = &mut sets[used.set as usize];
+
+            match set.iter_mut().find(|binding| binding.binding == used.binding) {
+                Some(binding) => {
+                    if binding.descriptor_type != used.descriptor_type || binding.descriptor_count != used.descriptor_count {
+                        return Err(ReverieError::InterfaceMismatch(format!(
+                            "the stages disagree about set {} binding {} ({})", used.set, used.binding, used.name
+                        )));
+                    }
+                    binding.stage_flags |= stage;
+                },
+                None => set.push(vk::DescriptorSetLayoutBinding::builder()
+                    .binding(used.binding)
+                    .descriptor_type(used.descriptor_type)
+                    .descriptor_count(used.descriptor_count)
+                    .stage_flags(stage)
+                    .build())
+            }
+        }
+
+        Ok(Self {
+            vertex_inputs: vertex.inputs.clone(),
+            push_constant_blocks,
+            sets
+        })
+    }
+
+    /// A single binding with the inputs tightly packed in location order, for vertex types
+    /// that declare their fields just like the shader does.
+    pub fn packed_vertex_layout(&self) -> Result<(Vec<vk::VertexInputBindingDescription>, Vec<vk::VertexInputAttributeDescription>), ReverieError> {
+        if self.vertex_inputs.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+
+        let mut attributes = vec![];
+        let mut offset = 0;
+        for input in &self.vertex_inputs {
+            let size = format_size(input.format)
+                .ok_or_else(|| ReverieError::Shader(format!("cannot derive a vertex layout for {} at location {}", input.name, input.location)))?;
+            attributes.push(vk::VertexInputAttributeDescription {
+                binding: 0,
+                location: input.location,
+                format: input.format,
+                offset
+            });
+            offset += size;
+        }
+
+        let bindings = vec![vk::VertexInputBindingDescription {
+            binding: 0,
+            stride: offset,
+            input_rate: vk::VertexInputRate::VERTEX
+        }];
+
+        Ok((bindings, attributes))
+    }
+
+    /// A single range covering the blocks of all stages.
+    pub fn push_constant_ranges(&self) -> Vec<vk::PushConstantRange> {
+        let Some(offset) = self.push_constant_blocks.iter().map(|block| block.offset).min() else {
+            return vec![];
+        };
+        let end = self.push_constant_blocks.iter().map(|block| block.offset + block.size).max().unwrap_or(offset);
+
+        vec![vk::PushConstantRange {
+            stage_flags: self.push_constant_blocks.iter().fold(vk::ShaderStageFlags::empty(), |flags, block| flags | block.stage_flags),
+            offset,
+            size: end - offset
+        }]
+    }
+
+    /// Every shader input needs an attribute at its location with the same number of components
+    /// and the same kind of numbers, so `UNORM` colors may feed a `vec4` but not a `vec3`.
+    /// Attributes the shader does not read are fine.
+    pub fn validate_vertex_layout(&self, attributes: &[vk::VertexInputAttributeDescription]) -> Result<(), ReverieError> {
+        for input in &self.vertex_inputs {
+            let attribute = attributes.iter().find(|attribute| attribute.location == input.location)
+                .ok_or_else(|| ReverieError::InterfaceMismatch(format!(
+                    "the vertex shader reads {} at location {}, but the vertex layout has no attribute there",
+                    input.name, input.location
+                )))?;
+
+            if let (Some(expected), Some(actual)) = (format_class(input.format), format_class(attribute.format)) {
+                if expected != actual {
+                    return Err(ReverieError::InterfaceMismatch(format!(
+                        "the vertex shader reads {} at location {} as {:?}, but the attribute is {:?}",
+                        input.name, input.location, input.format, attribute.format
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Each block has to be covered by ranges including its stage, so the shaders never read
+    /// past the data pushed from Rust. Ranges may be larger than the blocks.
+    pub fn validate_push_constants(&self, ranges: &[vk::PushConstantRange]) -> Result<(), ReverieError> {
+        for block in &self.push_constant_blocks {
+            let block_end = block.offset + block.size;
+            let covered_end = ranges.iter()
+                .filter(|range| range.stage_flags.contains(block.stage_flags) && range.offset <= block.offset)
+                .map(|range| range.offset + range.size)
+                .max();
+
+            match covered_end {
+                Some(end) if end >= block_end => (),
+                Some(end) => return Err(ReverieError::InterfaceMismatch(format!(
+                    "the {:?} push constant block ends at byte {}, but the push constant ranges only cover {} bytes",
+                    block.stage_flags, block_end, end
+                ))),
+                None => return Err(ReverieError::InterfaceMismatch(format!(
+                    "the {:?} stage has a push constant block, but no push constant range for it",
+                    block.stage_flags
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the bindings of `set` against the layout it will be created with. The layout may
+    /// have more bindings, and wider stage flags, than the shaders use.
+    pub fn validate_set_layout(&self, set: u32, layout_bindings: &[vk::DescriptorSetLayoutBinding]) -> Result<(), ReverieError> {
+        let Some(used_bindings) = self.sets.get(set as usize) else {
+            return Ok(());
+        };
+
+        for used in used_bindings {
+            let binding = layout_bindings.iter().find(|binding| binding.binding == used.binding)
+                .ok_or_else(|| ReverieError::InterfaceMismatch(format!(
+                    "the shaders use set {} binding {}, but the descriptor set layout has no such binding", set, used.binding
+                )))?;
+
+            if binding.descriptor_type != used.descriptor_type {
+                return Err(ReverieError::InterfaceMismatch(format!(
+                    "the shaders use set {} binding {} as {:?}, but the descriptor set layout has {:?}",
+                    set, used.binding, used.descriptor_type, binding.descriptor_type
+                )));
+            }
+            if binding.descriptor_count < used.descriptor_count {
+                return Err(ReverieError::InterfaceMismatch(format!(
+                    "the shaders use {} descriptors at set {} binding {}, but the descriptor set layout only has {}",
+                    used.descriptor_count, set, used.binding, binding.descriptor_count
+                )));
+            }
+            if !binding.stage_flags.contains(used.stage_flags) {
+                return Err(ReverieError::InterfaceMismatch(format!(
+                    "set {} binding {} is used by {:?}, but the descriptor set layout only makes it visible to {:?}",
+                    set, used.binding, used.stage_flags, binding.stage_flags
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 use ash::vk;
 
+use super::error::ReverieError;
+
 pub struct RenderPass {}
 
 impl RenderPass {
-    pub fn init(logical_device: &ash::Device, format: vk::Format) -> Result<vk::RenderPass, vk::Result> {
+    pub fn init(logical_device: &ash::Device, format: vk::Format, final_layout: vk::ImageLayout, depth_format: vk::Format) -> Result<vk::RenderPass, ReverieError> {
         let attachments = [vk::AttachmentDescription::builder()
             .format(format)
             .load_op(vk::AttachmentLoadOp::CLEAR)
@@ -11,8 +13,18 @@ impl RenderPass {
             .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
             .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
             .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+            .final_layout(final_layout)
             .samples(vk::SampleCountFlags::TYPE_1) //No AA
+            .build(),
+            vk::AttachmentDescription::builder()
+            .format(depth_format)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+            .samples(vk::SampleCountFlags::TYPE_1)
             .build()
         ];
 
@@ -21,19 +33,33 @@ impl RenderPass {
             layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
         }];
 
+        let depth_attachment_reference = vk::AttachmentReference {
+            attachment: 1,
+            layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+        };
+
         let subpasses = [vk::SubpassDescription::builder()
             .color_attachments(&color_attachment_references)
+            .depth_stencil_attachment(&depth_attachment_reference)
             .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
             .build()
         ];
 
+        // Frames in flight can share the color and depth images, offscreen every frame draws into
+        // the same ones. Writes of the previous frame have to finish before this one writes again
         let subpass_dependencies = [vk::SubpassDependency::builder()
             .src_subpass(vk::SUBPASS_EXTERNAL)
-            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .src_stage_mask(
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS
+            )
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
             .dst_subpass(0)
-            .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .dst_stage_mask(
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+            )
             .dst_access_mask(
                 vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                    | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE
             )
             .build()
         ];
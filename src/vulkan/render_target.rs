@@ -0,0 +1,103 @@
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+
+use super::swapchain::VulkanSwapchain;
+use super::offscreen::OffscreenTarget;
+use super::error::ReverieError;
+
+/// Where a frame ends up: either presented through a window swapchain or kept in offscreen images.
+pub enum RenderTarget {
+    Swapchain(VulkanSwapchain),
+    Offscreen(OffscreenTarget),
+}
+
+impl RenderTarget {
+    pub fn extent(&self) -> vk::Extent2D {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.extent,
+            RenderTarget::Offscreen(offscreen) => offscreen.extent,
+        }
+    }
+
+    pub fn format(&self) -> vk::Format {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.surface_format.format,
+            RenderTarget::Offscreen(offscreen) => offscreen.format,
+        }
+    }
+
+    /// The format of the color attachment of the scene render pass.
+    pub fn scene_format(&self) -> vk::Format {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.scene_format(),
+            RenderTarget::Offscreen(offscreen) => offscreen.format,
+        }
+    }
+
+    pub fn framebuffers(&self) -> &[vk::Framebuffer] {
+        match self {
+            RenderTarget::Swapchain(swapchain) => &swapchain.framebuffers,
+            RenderTarget::Offscreen(offscreen) => &offscreen.framebuffers,
+        }
+    }
+
+    pub fn image_count(&self) -> usize {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.image_count,
+            RenderTarget::Offscreen(offscreen) => offscreen.image_count,
+        }
+    }
+
+    /// The offscreen image holding the most recently finished frame and the layout it was left in,
+    /// if anything has been drawn yet. Presented swapchain images belong to the presentation
+    /// engine and can't be read back from here, see `VulkanRenderer::request_capture`.
+    pub fn last_rendered_image(&self) -> Option<(vk::Image, vk::ImageLayout)> {
+        match self {
+            RenderTarget::Swapchain(_) => None,
+            RenderTarget::Offscreen(offscreen) => offscreen.rendered_image
+                .map(|index| (offscreen.images[index].image, self.final_layout()))
+        }
+    }
+
+    /// Layout the presented or read back image is left in once the frame is finished.
+    pub fn final_layout(&self) -> vk::ImageLayout {
+        match self {
+            RenderTarget::Swapchain(_) => vk::ImageLayout::PRESENT_SRC_KHR,
+            RenderTarget::Offscreen(_) => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        }
+    }
+
+    /// Layout the scene render pass leaves its color attachment in, ready for the output pass if there is one.
+    pub fn scene_final_layout(&self) -> vk::ImageLayout {
+        match self {
+            RenderTarget::Swapchain(swapchain) if swapchain.output_encoding().needs_output_pass() => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            _ => self.final_layout()
+        }
+    }
+
+    /// Creates the per-image depth attachments and the framebuffers that use them.
+    pub fn create_framebuffers(&mut self, logical_device: &ash::Device, allocator: &mut Allocator, renderpass: vk::RenderPass, depth_format: vk::Format) -> Result<(), ReverieError> {
+        match self {
+            RenderTarget::Swapchain(swapchain) => {
+                swapchain.create_depth_images(logical_device, allocator, depth_format)?;
+                swapchain.create_scene_images(logical_device, allocator)?;
+                swapchain.create_framebuffers(logical_device, renderpass)
+            },
+            RenderTarget::Offscreen(offscreen) => {
+                offscreen.create_depth_images(logical_device, allocator, depth_format)?;
+                offscreen.create_framebuffers(logical_device, renderpass)
+            },
+        }
+    }
+
+    pub unsafe fn cleanup(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.cleanup(logical_device, allocator),
+            RenderTarget::Offscreen(offscreen) => offscreen.cleanup(logical_device, allocator),
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(self, RenderTarget::Offscreen(_))
+    }
+}
@@ -7,99 +7,307 @@ use super::debug::VulkanDebug;
 use super::physical_device::PhysicalDevice;
 use super::queue::*;
 use super::logical_device::LogicalDevice;
-use super::swapchain::VulkanSwapchain;
+use super::swapchain::{SwapchainConfig, VulkanSwapchain};
+use super::offscreen::OffscreenTarget;
+use super::render_target::RenderTarget;
 use super::render_pass::RenderPass;
-use super::pipeline::Pipeline;
+use super::output::{HdrSettings, OutputPass};
+use super::pipeline::{Pipeline, PipelineBuilder, PipelineHandle};
+use super::pipeline_cache::{default_cache_dir, PipelineCache};
+use super::shader::{ShaderCompiler, ShaderWatcher};
 use super::command_pools::Pools;
-use super::game_object::GameObject;
+use super::game_object::{save_previous, Color, GameObject, Transform2DComponent, Transform3DComponent};
+use super::scene::{self, GlobalTransform2D, GlobalTransform3D};
+use super::camera::Camera;
+use super::screenshot::Screenshot;
+use super::upload::Uploader;
+use super::mesh::Mesh;
+use super::frame::FrameData;
+use super::buffer::Buffer;
+use super::texture::{Texture, TextureHandle, TextureOptions};
+use super::descriptor::{layout_binding, DescriptorAllocator, DescriptorLayoutCache, DescriptorWriter};
+use super::error::ReverieError;
 
-use crate::utils::{align, any_as_u8_slice};
+use crate::ecs::entity::Entity;
+use crate::ecs::world::World;
+use crate::utils::{align, any_as_u8_slice, color};
+
+const OFFSCREEN_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+// One image is enough when nothing is presented, and keeps re-recording and readback free of races
+const OFFSCREEN_IMAGE_COUNT: usize = 1;
+/// Lets the CPU record one frame while the GPU works on the previous one.
+pub const DEFAULT_FRAMES_IN_FLIGHT: usize = 2;
+/// Every texture takes one set, textures are loaded in bulk so the pools can be large.
+const TEXTURE_SETS_PER_POOL: u32 = 256;
 
 pub struct VulkanRenderer {
     pub entry: ash::Entry,
     pub instance: ash::Instance,
+    /// Set by `resize` and `set_swapchain_config`, the swapchain is recreated before the next frame.
     pub is_framebuffer_resized: bool,
+    /// The size of the window in physical pixels, used when the surface leaves the extent up to the swapchain.
+    pub window_extent: vk::Extent2D,
+    /// Applied whenever the swapchain is recreated, change it through `set_swapchain_config`.
+    pub swapchain_config: SwapchainConfig,
     pub debug: VulkanDebug,
-    pub surface: VulkanSurface,
+    pub surface: Option<VulkanSurface>,
     pub physical_device: vk::PhysicalDevice,
     pub physical_device_properties: vk::PhysicalDeviceProperties,
     pub physical_device_features: vk::PhysicalDeviceFeatures,
+    /// The subset of `physical_device_features` the logical device was created with.
+    pub enabled_features: vk::PhysicalDeviceFeatures,
     pub queue_families: QueueFamilies,
     pub queues: Queues,
     pub device: ash::Device,
-    pub swapchain: VulkanSwapchain,
+    pub target: RenderTarget,
+    pub depth_format: vk::Format,
     pub renderpass: vk::RenderPass,
-    pub pipeline: Pipeline,
+    /// Encodes the frame for surfaces without an `_SRGB` format, drawing the scene images into the
+    /// swapchain images. Recreated along with the render pass.
+    pub output_pass: Option<OutputPass>,
+    /// Used by the output pass on HDR surfaces, takes effect on the next frame.
+    pub hdr_settings: HdrSettings,
+    /// The built-in pipelines at the indices of the `PipelineHandle` constants, followed by the ones
+    /// added with `create_pipeline`. They are rebuilt from their builders along with the render pass.
+    pub pipelines: Vec<(PipelineBuilder, Pipeline)>,
+    /// Saved to disk when the renderer is dropped.
+    pub pipeline_cache: PipelineCache,
+    pub shader_compiler: Option<ShaderCompiler>,
+    /// Set while shader hot reload is enabled.
+    pub shader_watcher: Option<ShaderWatcher>,
+    pub descriptor_layouts: DescriptorLayoutCache,
+    /// Set 0 of every pipeline, holds `GlobalUniformData` at binding 0.
+    pub global_set_layout: vk::DescriptorSetLayout,
+    /// Set 1 of the textured pipeline.
+    pub texture_set_layout: vk::DescriptorSetLayout,
+    pub texture_descriptors: DescriptorAllocator,
+    pub textures: Vec<Texture>,
     pub pools: Pools,
-    pub command_buffers: Vec<vk::CommandBuffer>,
+    pub uploader: Uploader,
+    pub frames: Vec<FrameData>,
+    pub current_frame: usize,
     pub allocator: std::mem::ManuallyDrop<Allocator>,
-    pub game_objects: Vec<GameObject>
+    pub camera: Camera,
+    /// Everything drawn is an entity in here, see `GameObject` for the components it needs.
+    pub world: World,
+    /// How far the frame is between the previous and the current fixed step, game objects are
+    /// drawn blended between the two. 1.0 draws them as they are.
+    pub interpolation_alpha: f32,
+    /// Set by `request_capture`, the next frame drawn to the swapchain is copied out.
+    capture_requested: bool,
+    /// Holds the copy of the last captured swapchain frame until `capture_frame` reads it.
+    captured_frame: Option<Buffer>
 }
 
 impl VulkanRenderer {
-    pub fn new(window: &VulkanWindow) -> Result<Self, Box<dyn std::error::Error>> {
-        let layer_names = vec!["VK_LAYER_KHRONOS_validation"]; 
-        let entry = ash::Entry::linked();
-        let instance = Self::create_instance(&entry, &layer_names, &window)
-            .expect("Failed to initialize instance!");
-        
-        let debug = VulkanDebug::new(&entry, &instance)?;
+    pub fn new(window: &VulkanWindow) -> Result<Self, ReverieError> {
+        Self::with_swapchain_config(window, SwapchainConfig::default())
+    }
+
+    pub fn with_swapchain_config(window: &VulkanWindow, swapchain_config: SwapchainConfig) -> Result<Self, ReverieError> {
+        Self::init(Some(window), window.extent(), swapchain_config)
+    }
+
+    /// Creates a renderer that draws into offscreen images instead of a window swapchain.
+    /// No surface or presentation support is needed, so this also runs on software
+    /// implementations like lavapipe. The pipeline cache is not loaded from or saved to disk.
+    pub fn new_headless(width: u32, height: u32) -> Result<Self, ReverieError> {
+        Self::init(None, vk::Extent2D { width, height }, SwapchainConfig::default())
+    }
 
-        let surface = VulkanSurface::new(&window, &entry, &instance)?;
+    fn init(window: Option<&VulkanWindow>, extent: vk::Extent2D, swapchain_config: SwapchainConfig) -> Result<Self, ReverieError> {
+        let entry = ash::Entry::linked();
+        let layer_names = Self::available_layers(&entry, &["VK_LAYER_KHRONOS_validation"]);
+        let instance = Self::create_instance(&entry, &layer_names, window)?;
+        let mut guard = InitGuard::new(&instance);
 
-        let (physical_device, physical_device_properties, physical_device_features) = PhysicalDevice::pick_physical_device(&instance)
-            .expect("No suitable physical device found!");
+        let debug = VulkanDebug::new(&entry, &instance)?;
+        guard.debug = Some(debug.clone());
 
-        let queue_families = QueueFamilies::new(&instance, physical_device, &surface)?;
+        let surface = match window {
+            Some(window) => Some(VulkanSurface::new(window, &entry, &instance)?),
+            None => None
+        };
+        guard.surface = surface.clone();
 
-        let (logical_device, queues) = LogicalDevice::new(&instance, physical_device, &queue_families, &layer_names)?;
+        let (physical_device, physical_device_properties, physical_device_features) = PhysicalDevice::pick_physical_device(&instance)?;
 
-        let mut swapchain = VulkanSwapchain::new(&instance, physical_device, &logical_device, &surface, &queue_families)?;
+        let depth_format = PhysicalDevice::find_depth_format(&instance, physical_device)
+            .ok_or_else(|| ReverieError::DeviceSelection("no supported depth format".into()))?;
 
-        let renderpass = RenderPass::init(&logical_device, swapchain.surface_format.format)?;
+        let queue_families = QueueFamilies::new(&instance, physical_device, surface.as_ref())?;
 
-        swapchain.create_framebuffers(&logical_device, renderpass)?;
+        let device_extensions = match surface {
+            Some(_) => vec![ash::extensions::khr::Swapchain::name()],
+            None => vec![]
+        };
 
-        let pipeline = Pipeline::new(&logical_device, &swapchain, &renderpass)?;
+        // Optional features for wireframe and wide line pipelines, only enabled where supported
+        let enabled_features = vk::PhysicalDeviceFeatures {
+            fill_mode_non_solid: physical_device_features.fill_mode_non_solid,
+            wide_lines: physical_device_features.wide_lines,
+            ..Default::default()
+        };
 
-        let pools = Pools::new(&logical_device, &queue_families)?;
+        let (logical_device, queues) = LogicalDevice::new(&instance, physical_device, &queue_families, &layer_names, &device_extensions, &enabled_features)?;
+        guard.device = Some(logical_device.clone());
 
         let buffer_device_address = false;
-        let allocator = Allocator::new(&AllocatorCreateDesc {
+        let mut allocator = Allocator::new(&AllocatorCreateDesc {
             instance: instance.clone(),
             device: logical_device.clone(),
             physical_device,
             debug_settings: Default::default(),
             buffer_device_address,
-        }).expect("Failed to create allocator!");
+        })?;
         allocator.report_memory_leaks(log::Level::Info);
 
-        let command_buffers = Self::create_commandbuffers(&logical_device, &pools, swapchain.image_count)?;
+        let mut target = match &surface {
+            Some(surface) => RenderTarget::Swapchain(
+                VulkanSwapchain::new(&instance, physical_device, &logical_device, surface, &queue_families, &swapchain_config, extent, vk::SwapchainKHR::null())?
+            ),
+            None => RenderTarget::Offscreen(
+                OffscreenTarget::new(&logical_device, &mut allocator, extent, OFFSCREEN_FORMAT, OFFSCREEN_IMAGE_COUNT)?
+            )
+        };
+
+        let renderpass = RenderPass::init(&logical_device, target.scene_format(), target.scene_final_layout(), depth_format)?;
 
-        
+        target.create_framebuffers(&logical_device, &mut allocator, renderpass, depth_format)?;
+
+        // Headless renderers are mostly tests and tools, they keep their cache in memory instead
+        // of sharing the user's cache file
+        let cache_dir = window.map(|_| default_cache_dir());
+        let pipeline_cache = PipelineCache::new(&logical_device, &physical_device_properties, cache_dir.as_deref())?;
+
+        let mut descriptor_layouts = DescriptorLayoutCache::new();
+        let global_set_layout = Self::global_set_layout(&mut descriptor_layouts, &logical_device)?;
+        let texture_set_layout = Texture::descriptor_set_layout(&mut descriptor_layouts, &logical_device)?;
+
+        // In the order of the `PipelineHandle` constants
+        let pipelines = [
+            PipelineBuilder::basic_2d().descriptor_set_layouts(&[global_set_layout]),
+            PipelineBuilder::textured_2d().descriptor_set_layouts(&[global_set_layout, texture_set_layout]),
+            PipelineBuilder::basic_3d().descriptor_set_layouts(&[global_set_layout])
+        ]
+            .into_iter()
+            .map(|builder| {
+                let pipeline = builder.build(&logical_device, &enabled_features, renderpass, &mut descriptor_layouts, pipeline_cache.cache)?;
+                Ok((builder, pipeline))
+            })
+            .collect::<Result<Vec<_>, ReverieError>>()?;
+
+        let output_pass = Self::create_output_pass(&logical_device, &mut target, &mut descriptor_layouts, pipeline_cache.cache)?;
+
+        let pools = Pools::new(&logical_device, &queue_families)?;
+
+        let uploader = Uploader::new(&logical_device, &pools, &queue_families)?;
+
+        let frames = FrameData::create_frames(&logical_device, &mut allocator, queue_families.graphics.unwrap(), DEFAULT_FRAMES_IN_FLIGHT)?;
+
+        let mut camera = Camera::default();
+        camera.set_aspect_ratio(target.extent());
+
+        guard.is_complete = true;
         Ok(Self {
             entry,
             instance,
             is_framebuffer_resized: false,
+            window_extent: extent,
+            swapchain_config,
             debug,
             surface,
             physical_device,
             physical_device_properties,
             physical_device_features,
+            enabled_features,
             queue_families,
             queues,
             device: logical_device,
-            swapchain,
+            target,
+            depth_format,
             renderpass,
-            pipeline,
+            output_pass,
+            hdr_settings: HdrSettings::default(),
+            pipelines,
+            shader_compiler: None,
+            shader_watcher: None,
+            pipeline_cache,
+            descriptor_layouts,
+            global_set_layout,
+            texture_set_layout,
+            texture_descriptors: DescriptorAllocator::new(TEXTURE_SETS_PER_POOL),
+            textures: vec![],
             pools,
-            command_buffers,
+            uploader,
+            frames,
+            current_frame: 0,
             allocator: std::mem::ManuallyDrop::new(allocator),
-            game_objects: vec![]
+            camera,
+            world: World::new(),
+            interpolation_alpha: 1.0,
+            capture_requested: false,
+            captured_frame: None
         })
     }
 
-    pub fn create_instance(entry: &ash::Entry, layer_names: &[&str], window: &VulkanWindow) -> Result<ash::Instance, vk::Result> {
+    /// The global uniforms are read by vertex and fragment shaders alike.
+    fn global_set_layout(layout_cache: &mut DescriptorLayoutCache, logical_device: &ash::Device) -> Result<vk::DescriptorSetLayout, ReverieError> {
+        layout_cache.get_or_create(logical_device, &[
+            layout_binding(0, vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+        ])
+    }
+
+    /// Creates the output pass and its framebuffers if the surface format needs one.
+    fn create_output_pass(
+        logical_device: &ash::Device,
+        target: &mut RenderTarget,
+        layout_cache: &mut DescriptorLayoutCache,
+        pipeline_cache: vk::PipelineCache,
+    ) -> Result<Option<OutputPass>, ReverieError> {
+        let swapchain = match target {
+            RenderTarget::Swapchain(swapchain) if swapchain.output_encoding().needs_output_pass() => swapchain,
+            _ => return Ok(None)
+        };
+
+        println!("[Reverie][info] Encoding output for {:?} with an output pass", swapchain.surface_format);
+        let mut output_pass = OutputPass::new(logical_device, swapchain.surface_format, layout_cache, pipeline_cache)?;
+        if let Err(err) = Self::attach_output_pass(logical_device, target, &mut output_pass) {
+            output_pass.destroy(logical_device);
+            return Err(err);
+        }
+        Ok(Some(output_pass))
+    }
+
+    /// Points the output pass at the current scene images and swapchain images.
+    fn attach_output_pass(logical_device: &ash::Device, target: &mut RenderTarget, output_pass: &mut OutputPass) -> Result<(), ReverieError> {
+        if let RenderTarget::Swapchain(swapchain) = target {
+            swapchain.create_output_framebuffers(logical_device, output_pass.renderpass)?;
+            let scene_views: Vec<vk::ImageView> = swapchain.scene_images.iter().map(|image| image.imageview).collect();
+            output_pass.update_descriptor_sets(logical_device, &scene_views)?;
+        }
+        Ok(())
+    }
+
+    fn available_layers<'a>(entry: &ash::Entry, layer_names: &[&'a str]) -> Vec<&'a str> {
+        let layer_properties = entry.enumerate_instance_layer_properties().unwrap_or_default();
+
+        layer_names
+            .iter()
+            .filter(|&&name| {
+                let available = layer_properties.iter().any(|layer| {
+                    unsafe { std::ffi::CStr::from_ptr(layer.layer_name.as_ptr()) }.to_str() == Ok(name)
+                });
+                if !available {
+                    println!("[Reverie][warn] Layer {} is not available, continuing without it.", name);
+                }
+                available
+            })
+            .copied()
+            .collect()
+    }
+
+    pub fn create_instance(entry: &ash::Entry, layer_names: &[&str], window: Option<&VulkanWindow>) -> Result<ash::Instance, ReverieError> {
         let app_name = std::ffi::CString::new("Reverie Engine").unwrap();
         let engine_name = std::ffi::CString::new("Reverie").unwrap();
 
@@ -124,12 +332,24 @@ impl VulkanRenderer {
             vec![
                 ash::extensions::ext::DebugUtils::name().as_ptr(),
             ];
-        let required_surface_extensions = ash_window::enumerate_required_extensions(&window.window)
-            .unwrap()
-            .iter()
-            .map(|ext| *ext)
-            .collect::<Vec<*const i8>>();
-        extension_name_pointers.extend(required_surface_extensions.iter());
+        if let Some(window) = window {
+            let required_surface_extensions = ash_window::enumerate_required_extensions(&window.window)
+                .map_err(ReverieError::Surface)?
+                .iter()
+                .map(|ext| *ext)
+                .collect::<Vec<*const i8>>();
+            extension_name_pointers.extend(required_surface_extensions.iter());
+
+            // Makes the HDR color spaces available to the swapchain where the driver has them
+            let colorspace_extension = vk::ExtSwapchainColorspaceFn::name();
+            let is_available = entry.enumerate_instance_extension_properties(None)
+                .unwrap_or_default()
+                .iter()
+                .any(|ext| unsafe { std::ffi::CStr::from_ptr(ext.extension_name.as_ptr()) } == colorspace_extension);
+            if is_available {
+                extension_name_pointers.push(colorspace_extension.as_ptr());
+            }
+        }
 
         println!("Extensions in use: ");
         for ext in extension_name_pointers.iter() {
@@ -144,241 +364,782 @@ impl VulkanRenderer {
             .enabled_extension_names(&extension_name_pointers)
             .flags(create_flags);
 
-        unsafe { entry.create_instance(&create_info, None) }
+        Ok(unsafe { entry.create_instance(&create_info, None)? })
     }
 
-    pub fn recreate_swapchain(&mut self) {
-        unsafe {
-            self.device 
-                .device_wait_idle()
-                .expect("Failed to wait device idle (recreate swapchain)!")
+    /// Tells the renderer about the new size of the window in physical pixels. Call this on
+    /// `WindowEvent::Resized`, a size of zero pauses rendering until the window is restored.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.window_extent = vk::Extent2D { width, height };
+        self.is_framebuffer_resized = true;
+    }
+
+    /// Recreates the swapchain with `config` before the next frame.
+    pub fn set_swapchain_config(&mut self, config: SwapchainConfig) {
+        self.swapchain_config = config;
+        self.is_framebuffer_resized = true;
+    }
+
+    /// Switches between `FIFO` and the fastest present mode the surface supports.
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.set_swapchain_config(self.swapchain_config.clone().with_vsync(vsync));
+    }
+
+    /// Whether the window has no area to draw to, `draw_frame` does nothing meanwhile.
+    pub fn is_minimized(&self) -> bool {
+        !self.target.is_headless() && (self.window_extent.width == 0 || self.window_extent.height == 0)
+    }
+
+    /// Replaces the swapchain after a resize, together with its image views, depth images and
+    /// framebuffers. Pipelines, pools and the uploader are kept: viewport and scissor are dynamic,
+    /// so only the render pass would have to change, and only if the surface format did.
+    ///
+    /// While the surface has a size of zero nothing is recreated, `is_framebuffer_resized` stays
+    /// set so the next frame tries again. The flag is only cleared once everything has been
+    /// rebuilt, if creating the new swapchain fails the old one is left as it was.
+    pub fn recreate_swapchain(&mut self) -> Result<(), ReverieError> {
+        // Offscreen targets have a fixed size and are never out of date
+        let (surface, old_swapchain) = match (&self.surface, &mut self.target) {
+            (Some(surface), RenderTarget::Swapchain(swapchain)) => (surface, swapchain),
+            _ => return Ok(())
         };
 
-        unsafe {
-            self.device.free_command_buffers(self.pools.graphics_command_pool, &self.command_buffers);
-            self.pools.cleanup(&self.device);
-            self.pipeline.cleanup(&self.device);
+        let capabilities = surface.get_capabilities(self.physical_device)?;
+        let extent = VulkanSwapchain::choose_extent(&capabilities, self.window_extent);
+        if extent.width == 0 || extent.height == 0 {
+            self.is_framebuffer_resized = true;
+            return Ok(());
+        }
+
+        let swapchain = VulkanSwapchain::new(&self.instance, self.physical_device, &self.device, surface, &self.queue_families, &self.swapchain_config, self.window_extent, old_swapchain.swapchain)?;
+        let old_surface_format = old_swapchain.surface_format;
+
+        // The old swapchain is retired now, it only has to be destroyed once its frames are done
+        unsafe { self.device.device_wait_idle()? };
+        if let RenderTarget::Swapchain(mut old_swapchain) = std::mem::replace(&mut self.target, RenderTarget::Swapchain(swapchain)) {
+            unsafe { old_swapchain.cleanup(&self.device, &mut self.allocator) };
+        }
+
+        let surface_format = match &self.target {
+            RenderTarget::Swapchain(swapchain) => swapchain.surface_format,
+            RenderTarget::Offscreen(_) => old_surface_format
+        };
+        let is_format_changed = surface_format != old_surface_format;
+        if is_format_changed {
+            println!("[Reverie][info] Surface format changed to {:?}, rebuilding the render pass and pipelines", surface_format);
+            let renderpass = RenderPass::init(&self.device, self.target.scene_format(), self.target.scene_final_layout(), self.depth_format)?;
             RenderPass::cleanup(&self.device, self.renderpass);
-            self.swapchain.cleanup(&self.device);
+            self.renderpass = renderpass;
+
+            for (builder, pipeline) in &mut self.pipelines {
+                let new_pipeline = builder.build(&self.device, &self.enabled_features, self.renderpass, &mut self.descriptor_layouts, self.pipeline_cache.cache)?;
+                pipeline.cleanup(&self.device);
+                *pipeline = new_pipeline;
+            }
+        }
+
+        self.target.create_framebuffers(&self.device, &mut self.allocator, self.renderpass, self.depth_format)?;
+
+        if is_format_changed {
+            if let Some(mut output_pass) = self.output_pass.take() {
+                output_pass.destroy(&self.device);
+            }
+            self.output_pass = Self::create_output_pass(&self.device, &mut self.target, &mut self.descriptor_layouts, self.pipeline_cache.cache)?;
+        } else if let Some(output_pass) = &mut self.output_pass {
+            Self::attach_output_pass(&self.device, &mut self.target, output_pass)?;
         }
 
-        self.swapchain = VulkanSwapchain::new(&self.instance, self.physical_device, &self.device, &self.surface, &self.queue_families)
-            .expect("Failed to recreate swapchain.");
+        self.camera.set_aspect_ratio(self.target.extent());
+        self.is_framebuffer_resized = false;
 
-        self.renderpass = RenderPass::init(&self.device, self.swapchain.surface_format.format)
-            .expect("Failed to recreate renderpass.");
+        Ok(())
+    }
 
-        self.swapchain.create_framebuffers(&self.device, self.renderpass)
-            .expect("Failed to recreate framebuffers.");
+    /// Adds the object to the world, it is drawn from the next frame on.
+    pub fn spawn(&mut self, game_object: GameObject) -> Entity {
+        game_object.spawn(&mut self.world)
+    }
 
-        self.pipeline = Pipeline::new(&self.device, &self.swapchain, &self.renderpass)
-            .expect("Failed to recreate pipeline.");
+    /// Removes the entity from the world and destroys its mesh. If it has one, this waits for the
+    /// device to be idle first, since frames in flight may still draw it.
+    /// Its children stay where they are as roots, see `despawn_recursive` to remove them too.
+    pub fn despawn(&mut self, entity: Entity) -> Result<bool, ReverieError> {
+        if let Some(mut mesh) = self.world.remove::<Mesh>(entity) {
+            unsafe { self.device.device_wait_idle()? };
+            mesh.destroy(&self.device, &mut self.allocator);
+        }
+        scene::unlink(&mut self.world, entity);
+        Ok(self.world.despawn(entity))
+    }
 
-        self.pools = Pools::new(&self.device, &self.queue_families)
-            .expect("Failed to recreate pipeline.");
+    /// Despawns the entity and everything attached below it, destroying all their meshes after
+    /// a single wait for the device. Returns how many entities were despawned.
+    pub fn despawn_recursive(&mut self, entity: Entity) -> Result<usize, ReverieError> {
+        let mut entities = vec![entity];
+        entities.extend(scene::descendants(&self.world, entity));
 
-        self.command_buffers = Self::create_commandbuffers(&self.device, &self.pools, self.swapchain.image_count)
-            .expect("Failed to recreate command_buffers.");
+        let mut meshes: Vec<Mesh> = entities.iter()
+            .filter_map(|&entity| self.world.remove::<Mesh>(entity))
+            .collect();
+        if !meshes.is_empty() {
+            unsafe { self.device.device_wait_idle()? };
+            for mesh in &mut meshes {
+                mesh.destroy(&self.device, &mut self.allocator);
+            }
+        }
 
-        Self::fill_commandbuffers(&self.command_buffers, &self.device, &self.renderpass, &self.swapchain, &self.pipeline, &self.game_objects)
-            .expect("Failed to fill commmandbuffers");
+        scene::unlink(&mut self.world, entity);
+        Ok(entities.into_iter().filter(|&entity| self.world.despawn(entity)).count())
     }
 
-    pub fn create_commandbuffers(logical_device: &ash::Device, pools: &Pools, amount: usize) -> Result<Vec<vk::CommandBuffer>, vk::Result> {
-        let commandbuffer_allocate_info = vk::CommandBufferAllocateInfo::builder()
-            .level(vk::CommandBufferLevel::PRIMARY)
-            .command_pool(pools.graphics_command_pool)
-            .command_buffer_count(amount as u32);
-            
-        
-        unsafe { logical_device.allocate_command_buffers(&commandbuffer_allocate_info) }
+    /// Saves the transforms of all entities as the state to interpolate from, see `game_object::save_previous`.
+    pub fn save_transforms(&mut self) {
+        save_previous::<Transform2DComponent>(&mut self.world);
+        save_previous::<Transform3DComponent>(&mut self.world);
     }
 
-    pub fn fill_commandbuffers(command_buffers: &[vk::CommandBuffer], logical_device: &ash::Device, renderpass: &vk::RenderPass, swapchain: &VulkanSwapchain, pipeline: &Pipeline, game_objects: &Vec<GameObject>
-    ) -> Result<(), vk::Result> {
-        unsafe {
-            logical_device
-                .wait_for_fences(&[swapchain.may_begin_drawing[swapchain.current_image]], true, std::u64::MAX)
-                .expect("Fence wait failed!");
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Changes how many frames the CPU may record ahead of the GPU. Waits for the device to be idle.
+    pub fn set_frames_in_flight(&mut self, count: usize) -> Result<(), ReverieError> {
+        assert!(count > 0, "At least one frame has to be in flight!");
+
+        unsafe { self.device.device_wait_idle()? };
+        for frame in &mut self.frames {
+            frame.destroy(&self.device, &mut self.allocator);
         }
+        self.frames = FrameData::create_frames(&self.device, &mut self.allocator, self.queue_families.graphics.unwrap(), count)?;
+        self.current_frame = 0;
 
-        for (i, &command_buffer) in command_buffers.iter().enumerate() {
-            let commandbuffer_begininfo = vk::CommandBufferBeginInfo::builder();
-            unsafe { logical_device.begin_command_buffer(command_buffer, &commandbuffer_begininfo)?; }
-        
+        Ok(())
+    }
+
+    /// Creates a host visible buffer for the next `draw_frame`, it stays alive until that frame
+    /// has finished on the GPU. May wait for an earlier frame to finish first.
+    pub fn create_transient_buffer(&mut self, size: u64, usage: vk::BufferUsageFlags) -> Result<&mut Buffer, ReverieError> {
+        self.frames[self.current_frame].create_transient_buffer(&self.device, &mut self.allocator, size, usage)
+    }
+
+    /// Starts from the default 2D pipeline, including its descriptor set layouts.
+    /// Change what is needed and pass it to `create_pipeline`.
+    pub fn pipeline_builder_2d(&self) -> PipelineBuilder {
+        self.pipelines[PipelineHandle::BASIC_2D.0].0.clone()
+    }
+
+    /// Like `pipeline_builder_2d`, for textured 2D objects.
+    pub fn pipeline_builder_textured(&self) -> PipelineBuilder {
+        self.pipelines[PipelineHandle::TEXTURED_2D.0].0.clone()
+    }
+
+    /// Like `pipeline_builder_2d`, for 3D objects.
+    pub fn pipeline_builder_3d(&self) -> PipelineBuilder {
+        self.pipelines[PipelineHandle::BASIC_3D.0].0.clone()
+    }
 
-            let clear_values = [vk::ClearValue {
-                color: vk::ClearColorValue {
-                    float32: [0.0, 0.0, 0.0, 1.0]
-                }}, 
-                vk::ClearValue {
-                depth_stencil: vk::ClearDepthStencilValue {
-                    depth: 1.0,
-                    stencil: 0
+    /// Builds a pipeline that entities can select with a `PipelineHandle` component, see `GameObject::pipeline`.
+    /// The pipeline lives as long as the renderer.
+    pub fn create_pipeline(&mut self, builder: PipelineBuilder) -> Result<PipelineHandle, ReverieError> {
+        if let (Some(watcher), Some(paths)) = (&mut self.shader_watcher, builder.shader_paths()) {
+            watcher.watch(&paths.vertex)?;
+            watcher.watch(&paths.fragment)?;
+        }
+
+        let pipeline = builder.build(&self.device, &self.enabled_features, self.renderpass, &mut self.descriptor_layouts, self.pipeline_cache.cache)?;
+        self.pipelines.push((builder, pipeline));
+        Ok(PipelineHandle(self.pipelines.len() - 1))
+    }
+
+    pub fn pipeline(&self, handle: PipelineHandle) -> &Pipeline {
+        &self.pipelines[handle.0].1
+    }
+
+    /// Loads shaders from disk, as GLSL or precompiled SPIR-V. Created on first use.
+    pub fn shader_compiler(&mut self) -> Result<&ShaderCompiler, ReverieError> {
+        if self.shader_compiler.is_none() {
+            self.shader_compiler = Some(ShaderCompiler::new()?);
+        }
+        Ok(self.shader_compiler.as_ref().unwrap())
+    }
+
+    /// Watches the shader files of all pipelines, including ones created later. When one changes,
+    /// `draw_frame` rebuilds the pipelines using it in place. If the shader fails to compile,
+    /// the error is logged and the previous pipeline stays in use.
+    pub fn enable_shader_hot_reload(&mut self) -> Result<(), ReverieError> {
+        self.shader_compiler()?;
+        let mut watcher = ShaderWatcher::new()?;
+
+        for (builder, _) in &self.pipelines {
+            if let Some(paths) = builder.shader_paths() {
+                for path in [&paths.vertex, &paths.fragment] {
+                    // The sources of the built-in shaders are missing when the engine is used as a packaged crate
+                    if let Err(err) = watcher.watch(path) {
+                        println!("[Reverie][warn] Not watching shader: {}", err);
+                    }
                 }
+            }
+        }
+
+        self.shader_watcher = Some(watcher);
+        Ok(())
+    }
+
+    pub fn disable_shader_hot_reload(&mut self) {
+        self.shader_watcher = None;
+    }
+
+    /// Rebuilds the pipelines whose shader files changed since the last call.
+    fn reload_changed_shaders(&mut self) -> Result<(), ReverieError> {
+        let (watcher, compiler) = match (&self.shader_watcher, &self.shader_compiler) {
+            (Some(watcher), Some(compiler)) => (watcher, compiler),
+            _ => return Ok(())
+        };
+
+        let changed = watcher.changed_files();
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        let mut is_idle = false;
+        for (builder, pipeline) in &mut self.pipelines {
+            if !builder.shader_paths().is_some_and(|paths| paths.is_affected_by(&changed)) {
+                continue;
+            }
+
+            let mut reloaded = builder.clone();
+            let result = reloaded.reload_shaders(compiler)
+                .and_then(|_| reloaded.build(&self.device, &self.enabled_features, self.renderpass, &mut self.descriptor_layouts, self.pipeline_cache.cache));
+
+            match result {
+                Ok(new_pipeline) => {
+                    // The old pipeline may still be used by frames in flight
+                    if !is_idle {
+                        unsafe { self.device.device_wait_idle()? };
+                        is_idle = true;
+                    }
+                    pipeline.cleanup(&self.device);
+                    *pipeline = new_pipeline;
+                    *builder = reloaded;
+                    if let Some(paths) = builder.shader_paths() {
+                        println!("[Reverie][info] Reloaded {} and {}", paths.vertex.display(), paths.fragment.display());
+                    }
+                },
+                Err(err) => println!("[Reverie][error] Shader reload failed, keeping the previous pipeline: {}", err)
+            }
+        }
+
+        Ok(())
+    }
+
+    fn object_pipeline(&self, entity: Entity, default: PipelineHandle) -> &Pipeline {
+        self.pipeline(self.world.get::<PipelineHandle>(entity).copied().unwrap_or(default))
+    }
+
+    /// The render system: every entity with a mesh and a 2D or 3D world transform, in the order
+    /// they were spawned so later objects are drawn over earlier ones at the same depth.
+    fn drawables(&self) -> Vec<(Entity, &Mesh)> {
+        let mut drawables: Vec<(Entity, &Mesh)> = self.world
+            .query::<Mesh>()
+            .filter(|&(entity, _)| self.world.has::<GlobalTransform2D>(entity) || self.world.has::<GlobalTransform3D>(entity))
+            .collect();
+        drawables.sort_unstable_by_key(|&(entity, _)| entity);
+        drawables
+    }
+
+    /// Records the draw commands for all game objects into `command_buffer`, rendering into the
+    /// image at `image_index`, followed by the output pass if there is one.
+    /// `global_set` is bound as set 0 of every pipeline. With a `capture_buffer`, the finished
+    /// swapchain image is copied into it before it is presented.
+    fn record_commands(&self, command_buffer: vk::CommandBuffer, image_index: usize, global_set: vk::DescriptorSet, capture_buffer: Option<vk::Buffer>) -> Result<(), ReverieError> {
+        let logical_device = &self.device;
+        let extent = self.target.extent();
+        let framebuffer = self.target.framebuffers()[image_index];
+
+        let commandbuffer_begininfo = vk::CommandBufferBeginInfo::builder()
+            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+        unsafe { logical_device.begin_command_buffer(command_buffer, &commandbuffer_begininfo)?; }
+
+        let clear_values = [vk::ClearValue {
+            color: vk::ClearColorValue {
+                float32: [0.0, 0.0, 0.0, 1.0]
+            }}, 
+            vk::ClearValue {
+            depth_stencil: vk::ClearDepthStencilValue {
+                depth: 1.0,
+                stencil: 0
+            }
+        }];
+
+        let renderpass_begininfo = vk::RenderPassBeginInfo::builder()
+            .render_pass(self.renderpass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x:0, y:0 },
+                extent
+            })
+            .clear_values(&clear_values);
+
+        unsafe {
+            logical_device.cmd_begin_render_pass(command_buffer, &renderpass_begininfo, vk::SubpassContents::INLINE);
+
+            let viewports = [vk::Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: extent.width as f32,
+                height: extent.height as f32,
+                min_depth: 0.0,
+                max_depth: 1.0,
             }];
 
-            let renderpass_begininfo = vk::RenderPassBeginInfo::builder()
-                .render_pass(*renderpass)
-                .framebuffer(swapchain.framebuffers[i])
-                .render_area(vk::Rect2D {
-                    offset: vk::Offset2D { x:0, y:0 },
-                    extent: swapchain.extent
-                })
-                .clear_values(&clear_values);
-
-            unsafe {
-                logical_device.cmd_begin_render_pass(command_buffer, &renderpass_begininfo, vk::SubpassContents::INLINE);
-
-                let viewports = [vk::Viewport {
-                    x: 0.0,
-                    y: 0.0,
-                    width: swapchain.extent.width as f32,
-                    height: swapchain.extent.height as f32,
-                    min_depth: 0.0,
-                    max_depth: 1.0,
-                }];
-
-                let scissors = [vk::Rect2D {
-                    offset: vk::Offset2D { x: 0, y: 0 },
-                    extent: swapchain.extent
-                }];
-                
-                logical_device.cmd_set_viewport(command_buffer, 0, &viewports);
-                logical_device.cmd_set_scissor(command_buffer, 0, &scissors);
-
-                for (_i, game_object) in game_objects.iter().enumerate() {
-                    logical_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline.pipeline);
-                    match &game_object.mesh.index_buffer {
-                        Some(index_buffer) => {
-                            logical_device.cmd_bind_index_buffer(command_buffer, index_buffer.get_buffer(), 0, vk::IndexType::UINT32);
-                            for vertex_buffer in &game_object.mesh.vertex_buffers {
-                                logical_device.cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer.get_buffer()], &[0]);
-
-                                    let push = PushConstantData {
-                                        _transform: game_object.transform2d.mat2(),
-                                        _offset: game_object.transform2d.translation,
-                                        _color: align::Align16(game_object.color)
-                                    };
-                                    let bytes = push.as_bytes();
-
-                                    logical_device.cmd_push_constants(command_buffer, pipeline.layout, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT, 0, &bytes);
-                                    logical_device.cmd_draw_indexed(command_buffer, index_buffer.get_index_count(), 1, 0, 0, 0);
-                                
-                            }
-                        },
-                        None => {
-                            for vertex_buffer in &game_object.mesh.vertex_buffers {
-                                logical_device.cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer.get_buffer()], &[0]);
-                                logical_device.cmd_draw(command_buffer, vertex_buffer.get_vertex_count(), 1, 0, 0);
+            let scissors = [vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent
+            }];
+            
+            logical_device.cmd_set_viewport(command_buffer, 0, &viewports);
+            logical_device.cmd_set_scissor(command_buffer, 0, &scissors);
+
+            for (entity, mesh) in self.drawables() {
+                let object_color = self.world.get::<Color>(entity).map_or(uv::Vec3::one(), |color| color.0);
+                let object_color = align::Align16(color::srgb_to_linear_rgb(object_color));
+
+                match self.world.get::<GlobalTransform3D>(entity) {
+                    Some(transform3d) => {
+                        let pipeline = self.object_pipeline(entity, PipelineHandle::BASIC_3D);
+                        logical_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline.pipeline);
+                        logical_device.cmd_bind_descriptor_sets(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline.layout, 0, &[global_set], &[]);
+
+                        let normal_matrix = transform3d.normal_matrix();
+                        let push = PushConstantData3D {
+                            _model: transform3d.matrix,
+                            _normal_matrix: normal_matrix.cols.map(align::Align16),
+                            _color: object_color
+                        };
+                        logical_device.cmd_push_constants(command_buffer, pipeline.layout, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT, 0, push.as_bytes());
+                    },
+                    None => {
+                        let Some(transform2d) = self.world.get::<GlobalTransform2D>(entity) else {
+                            continue;
+                        };
+
+                        let pipeline = match self.world.get::<TextureHandle>(entity) {
+                            Some(texture) => {
+                                let texture = &self.textures[texture.0];
+                                let pipeline = self.object_pipeline(entity, PipelineHandle::TEXTURED_2D);
+                                logical_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline.pipeline);
+                                logical_device.cmd_bind_descriptor_sets(
+                                    command_buffer,
+                                    vk::PipelineBindPoint::GRAPHICS,
+                                    pipeline.layout,
+                                    0,
+                                    &[global_set, texture.descriptor_set],
+                                    &[]
+                                );
+                                pipeline
+                            },
+                            None => {
+                                let pipeline = self.object_pipeline(entity, PipelineHandle::BASIC_2D);
+                                logical_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline.pipeline);
+                                logical_device.cmd_bind_descriptor_sets(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline.layout, 0, &[global_set], &[]);
+                                pipeline
                             }
+                        };
+
+                        let push = PushConstantData {
+                            _transform: transform2d.matrix,
+                            _offset: transform2d.translation,
+                            _depth: transform2d.depth,
+                            _color: object_color
+                        };
+                        logical_device.cmd_push_constants(command_buffer, pipeline.layout, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT, 0, push.as_bytes());
+                    }
+                }
+
+                match &mesh.index_buffer {
+                    Some(index_buffer) => {
+                        logical_device.cmd_bind_index_buffer(command_buffer, index_buffer.get_buffer(), 0, vk::IndexType::UINT32);
+                        for vertex_buffer in &mesh.vertex_buffers {
+                            logical_device.cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer.get_buffer()], &[0]);
+                            logical_device.cmd_draw_indexed(command_buffer, index_buffer.get_index_count(), 1, 0, 0, 0);
+                        }
+                    },
+                    None => {
+                        for vertex_buffer in &mesh.vertex_buffers {
+                            logical_device.cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer.get_buffer()], &[0]);
+                            logical_device.cmd_draw(command_buffer, vertex_buffer.get_vertex_count(), 1, 0, 0);
                         }
                     }
                 }
+            }
 
-                logical_device.cmd_end_render_pass(command_buffer);
-                logical_device.end_command_buffer(command_buffer)?;
+            logical_device.cmd_end_render_pass(command_buffer);
+
+            if let (Some(output_pass), RenderTarget::Swapchain(swapchain)) = (&self.output_pass, &self.target) {
+                output_pass.record(logical_device, command_buffer, swapchain.output_framebuffers[image_index], extent, image_index, &self.hdr_settings);
+            }
+
+            if let (Some(buffer), RenderTarget::Swapchain(swapchain)) = (capture_buffer, &self.target) {
+                Screenshot::record_copy(logical_device, command_buffer, swapchain.images[image_index], self.target.final_layout(), buffer, extent);
             }
+
+            logical_device.end_command_buffer(command_buffer)?;
         }
         Ok(())
     }
 
-    pub fn draw_frame(&mut self) {
-        self.swapchain.current_image = {self.swapchain.current_image + 1} % self.swapchain.image_count as usize;
+    /// Records and submits a frame of all game objects, then presents it when rendering to a window.
+    ///
+    /// Waits only for the GPU to finish the frame that last used the same `FrameData`,
+    /// so up to `frames_in_flight` frames can be queued at once.
+    ///
+    /// Nothing is drawn while the window is minimized.
+    pub fn draw_frame(&mut self) -> Result<(), ReverieError> {
+        if self.is_minimized() {
+            return Ok(());
+        }
 
-        let (image_index, _is_sub_optimal) = unsafe {
-            let result = self.swapchain.swapchain_loader.acquire_next_image(
-                self.swapchain.swapchain, std::u64::MAX, self.swapchain.image_available[self.swapchain.current_image], vk::Fence::null());
+        self.reload_changed_shaders()?;
 
-            match result {
-                Ok(image_index) => image_index,
-                Err(vk_result) => match vk_result {
-                    vk::Result::ERROR_OUT_OF_DATE_KHR => {
-                        self.recreate_swapchain();
-                        return;
-                    }
-                    _ => panic!("Failed to acquire swapchain image!")
+        if self.is_framebuffer_resized {
+            self.recreate_swapchain()?;
+            // Still no area to draw to
+            if self.is_framebuffer_resized {
+                return Ok(());
+            }
+        }
+
+        scene::propagate_transforms(&mut self.world, self.interpolation_alpha);
+
+        // Keeps transient buffers created for this frame since the last submission
+        self.frames[self.current_frame].begin(&self.device, &mut self.allocator)?;
+        let frame = &self.frames[self.current_frame];
+
+        let image_index = match &mut self.target {
+            RenderTarget::Swapchain(swapchain) => {
+                let result = unsafe {
+                    swapchain.swapchain_loader.acquire_next_image(
+                        swapchain.swapchain, std::u64::MAX, frame.image_available, vk::Fence::null())
+                };
+
+                match result {
+                    Ok((image_index, _is_sub_optimal)) => image_index as usize,
+                    Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+                        // Stays set if recreating fails, so the next frame tries again
+                        self.is_framebuffer_resized = true;
+                        return self.recreate_swapchain();
+                    },
+                    Err(vk_result) => return Err(ReverieError::Swapchain(vk_result))
                 }
+            },
+            RenderTarget::Offscreen(offscreen) => {
+                offscreen.current_image = (offscreen.current_image + 1) % offscreen.image_count;
+                offscreen.current_image
             }
         };
 
-        unsafe {
-            self.device.wait_for_fences(&[self.swapchain.may_begin_drawing[self.swapchain.current_image]], true, std::u64::MAX)
-                .expect("Fence wait failed!");
-        }
+        let frame = &mut self.frames[self.current_frame];
+        frame.global_uniforms.write(&GlobalUniformData::new(&self.camera))?;
+        let global_set = frame.descriptor_allocator.allocate(&self.device, self.global_set_layout)?;
+        DescriptorWriter::new()
+            .write_buffer(0, vk::DescriptorType::UNIFORM_BUFFER, frame.global_uniforms.descriptor_info())
+            .update(&self.device, global_set);
+
+        let capture_buffer = self.prepare_capture()?;
 
-        let semaphores_available = [self.swapchain.image_available[self.swapchain.current_image]];
+        let frame = &self.frames[self.current_frame];
+        self.record_commands(frame.command_buffer, image_index, global_set, capture_buffer)?;
+
+        let is_headless = self.target.is_headless();
+        let semaphores_available = [frame.image_available];
         let waiting_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-        let semaphores_finished = [self.swapchain.rendering_finished[self.swapchain.current_image]];
-        let command_buffers = [self.command_buffers[image_index as usize]];
-        let submit_info = [vk::SubmitInfo::builder()
-            .wait_semaphores(&semaphores_available)
-            .wait_dst_stage_mask(&waiting_stages)
-            .command_buffers(&command_buffers)
-            .signal_semaphores(&semaphores_finished)
-            .build()    
-        ];
+        let semaphores_finished = match &self.target {
+            RenderTarget::Swapchain(swapchain) => [swapchain.present_semaphores[image_index]],
+            RenderTarget::Offscreen(_) => [vk::Semaphore::null()]
+        };
+        let command_buffers = [frame.command_buffer];
+        let mut submit_info = vk::SubmitInfo::builder()
+            .command_buffers(&command_buffers);
+        // Nothing is acquired or presented offscreen, so there is nothing to wait for or signal
+        if !is_headless {
+            submit_info = submit_info
+                .wait_semaphores(&semaphores_available)
+                .wait_dst_stage_mask(&waiting_stages)
+                .signal_semaphores(&semaphores_finished);
+        }
 
         unsafe {
-            self.device.reset_fences(&[self.swapchain.may_begin_drawing[self.swapchain.current_image]])
-                .expect("Fence reset failed!");
-
-            self.device.queue_submit(self.queues.graphics_queue, &submit_info, self.swapchain.may_begin_drawing[self.swapchain.current_image])
-                .expect("Failed to submit command buffer!");
+            self.device.reset_fences(&[frame.in_flight])?;
+            self.device.queue_submit(self.queues.graphics_queue, &[submit_info.build()], frame.in_flight)?;
         }
+        self.frames[self.current_frame].submitted();
 
-        let swapchains = [self.swapchain.swapchain];
-        let indices = [image_index];
+        self.current_frame = (self.current_frame + 1) % self.frames.len();
+
+        let swapchain = match &mut self.target {
+            RenderTarget::Swapchain(swapchain) => swapchain,
+            RenderTarget::Offscreen(offscreen) => {
+                offscreen.rendered_image = Some(image_index);
+                return Ok(());
+            }
+        };
+
+        let swapchains = [swapchain.swapchain];
+        let indices = [image_index as u32];
         let present_info = vk::PresentInfoKHR::builder()
             .wait_semaphores(&semaphores_finished)
             .swapchains(&swapchains)
             .image_indices(&indices);
         
-        let result = unsafe { self.swapchain.swapchain_loader.queue_present(self.queues.graphics_queue, &present_info) };
+        let result = unsafe { swapchain.swapchain_loader.queue_present(self.queues.graphics_queue, &present_info) };
 
         let is_resized = match result {
-            Ok(_) => self.is_framebuffer_resized,
-            Err(vk_result) => match vk_result {
-                vk::Result::ERROR_OUT_OF_DATE_KHR | vk::Result::SUBOPTIMAL_KHR => true,
-                _ => panic!("Failed to present swapchain image")
-            }
+            Ok(is_sub_optimal) => is_sub_optimal || self.is_framebuffer_resized,
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => true,
+            Err(vk_result) => return Err(ReverieError::from(vk_result))
         };
 
         if is_resized {
-            self.is_framebuffer_resized = false;
-            self.recreate_swapchain();
+            self.is_framebuffer_resized = true;
+            self.recreate_swapchain()?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates a mesh in device local memory and waits until its data has been uploaded.
+    /// Use this for geometry that does not change after creation.
+    pub fn create_static_mesh<V: Copy>(&mut self, vertices: &[V], indices: &[u32]) -> Result<Mesh, ReverieError> {
+        let mesh = Mesh::new_static(&self.device, &mut self.allocator, &mut self.uploader, vertices, indices)?;
+        self.uploader.flush(&self.device, &mut self.allocator, &self.queues)?;
+        Ok(mesh)
+    }
+
+    /// Uploads `pixels` into a new texture and waits until it can be sampled.
+    /// The texture lives as long as the renderer.
+    pub fn create_texture(&mut self, pixels: &image::RgbaImage, options: TextureOptions) -> Result<TextureHandle, ReverieError> {
+        let linear_blit = PhysicalDevice::supports_linear_blit(&self.instance, self.physical_device, options.format());
+        if options.mipmaps && !linear_blit {
+            println!("[Reverie][warn] Device cannot blit {:?} with linear filtering, creating texture without mipmaps.", options.format());
+        }
+
+        let texture = Texture::new(
+            &self.device,
+            &mut self.allocator,
+            &mut self.uploader,
+            &mut self.texture_descriptors,
+            self.texture_set_layout,
+            pixels,
+            options,
+            linear_blit
+        )?;
+        self.textures.push(texture);
+        self.uploader.flush(&self.device, &mut self.allocator, &self.queues)?;
+
+        Ok(TextureHandle(self.textures.len() - 1))
+    }
+
+    /// Loads a PNG or JPEG file into a new texture, see `create_texture`.
+    pub fn load_texture<P: AsRef<std::path::Path>>(&mut self, path: P, options: TextureOptions) -> Result<TextureHandle, ReverieError> {
+        let pixels = Texture::decode_file(path)?;
+        self.create_texture(&pixels, options)
+    }
+
+    pub fn texture(&self, handle: TextureHandle) -> &Texture {
+        &self.textures[handle.0]
+    }
+
+    /// Makes the next frame drawn to the swapchain copy its image out before presenting it, for
+    /// `capture_frame`. Offscreen frames can always be captured and don't need this.
+    pub fn request_capture(&mut self) {
+        self.capture_requested = true;
+    }
+
+    /// Creates the buffer the frame about to be recorded is copied into, if a capture was requested.
+    fn prepare_capture(&mut self) -> Result<Option<vk::Buffer>, ReverieError> {
+        let RenderTarget::Swapchain(swapchain) = &self.target else {
+            return Ok(None);
+        };
+        if !std::mem::take(&mut self.capture_requested) {
+            return Ok(None);
+        }
+        if !swapchain.supports_readback {
+            println!("[Reverie][warn] The surface does not support TRANSFER_SRC, the frame cannot be captured.");
+            return Ok(None);
+        }
+
+        if let Some(mut previous) = self.captured_frame.take() {
+            // The previous capture may belong to a frame still in flight
+            unsafe { self.device.queue_wait_idle(self.queues.graphics_queue)? };
+            previous.destroy(&self.device, &mut self.allocator);
+        }
+
+        let buffer = Screenshot::create_staging_buffer(&self.device, &mut self.allocator, swapchain.extent)?;
+        let handle = buffer.get_buffer();
+        self.captured_frame = Some(buffer);
+        Ok(Some(handle))
+    }
+
+    /// Reads the most recently rendered frame back from the GPU as an RGBA8 image. When drawing
+    /// to a window, this is the last frame drawn after `request_capture`.
+    pub fn capture_frame(&mut self) -> Result<image::RgbaImage, ReverieError> {
+        if let RenderTarget::Swapchain(swapchain) = &self.target {
+            let mut buffer = self.captured_frame.take()
+                .ok_or_else(|| ReverieError::Readback("no frame has been drawn since request_capture".into()))?;
+
+            let result = unsafe { self.device.queue_wait_idle(self.queues.graphics_queue) }
+                .map_err(ReverieError::from)
+                .and_then(|_| Screenshot::to_rgba(&buffer, swapchain.surface_format.format, swapchain.extent));
+            buffer.destroy(&self.device, &mut self.allocator);
+            return result;
         }
+
+        let (image, layout) = self.target.last_rendered_image()
+            .ok_or_else(|| ReverieError::Readback("nothing rendered yet".into()))?;
+
+        unsafe { self.device.queue_wait_idle(self.queues.graphics_queue)? };
+
+        Screenshot::read_image(
+            &self.device,
+            &mut self.allocator,
+            self.pools.graphics_command_pool,
+            self.queues.graphics_queue,
+            image,
+            layout,
+            self.target.format(),
+            self.target.extent()
+        )
+    }
+
+    /// Captures the most recently rendered frame and writes it to `path` as a PNG.
+    pub fn save_screenshot<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<(), ReverieError> {
+        let image = self.capture_frame()?;
+        Screenshot::save_png(&image, path)
     }
 }
 
 impl Drop for VulkanRenderer {
     fn drop(&mut self) {
         unsafe {
-            self.device.device_wait_idle().expect("Failed to wait for device idle!");
+            // Nothing can be reported from here, and a lost device still has to be torn down
+            if let Err(err) = self.device.device_wait_idle() {
+                println!("[Reverie][error] Failed to wait for device idle while shutting down: {}", err);
+            }
+
+            for (_, mesh) in self.world.query_mut::<Mesh>() {
+                mesh.destroy(&self.device, &mut self.allocator);
+            }
 
-            for game_object in &mut self.game_objects {
-                game_object.mesh.destroy(&self.device, &mut self.allocator);
+            if let Some(buffer) = &mut self.captured_frame {
+                buffer.destroy(&self.device, &mut self.allocator);
             }
 
-            self.device.free_command_buffers(self.pools.graphics_command_pool, &self.command_buffers);
+            for frame in &mut self.frames {
+                frame.destroy(&self.device, &mut self.allocator);
+            }
+
+            for texture in &mut self.textures {
+                texture.destroy(&self.device, &mut self.allocator);
+            }
+            self.texture_descriptors.destroy(&self.device);
 
+            self.uploader.destroy(&self.device, &mut self.allocator);
             self.pools.cleanup(&self.device);
-            self.pipeline.cleanup(&self.device);
+            for (_, pipeline) in &self.pipelines {
+                pipeline.cleanup(&self.device);
+            }
+            if let Some(output_pass) = &mut self.output_pass {
+                output_pass.destroy(&self.device);
+            }
+            if let Err(err) = self.pipeline_cache.save(&self.device, &self.physical_device_properties) {
+                println!("[Reverie][warn] Failed to save the pipeline cache: {}", err);
+            }
+            self.pipeline_cache.destroy(&self.device);
+            self.descriptor_layouts.destroy(&self.device);
             self.device.destroy_render_pass(self.renderpass, None);
-            self.swapchain.cleanup(&self.device);
+            self.target.cleanup(&self.device, &mut self.allocator);
             std::mem::ManuallyDrop::drop(&mut self.allocator);
             self.device.destroy_device(None);
-            self.surface.cleanup();
+            if let Some(surface) = &mut self.surface {
+                surface.cleanup();
+            }
             self.debug.cleanup();
             self.instance.destroy_instance(None)
         };
     }
 }
 
+/// Destroys the device, surface, debug messenger and instance when `init` fails part way, in the
+/// order `Drop for VulkanRenderer` would. It holds copies of the handles, once `is_complete` is set
+/// the renderer owns the originals and nothing is destroyed.
+struct InitGuard {
+    instance: ash::Instance,
+    debug: Option<VulkanDebug>,
+    surface: Option<VulkanSurface>,
+    device: Option<ash::Device>,
+    is_complete: bool,
+}
+
+impl InitGuard {
+    fn new(instance: &ash::Instance) -> Self {
+        Self {
+            instance: instance.clone(),
+            debug: None,
+            surface: None,
+            device: None,
+            is_complete: false
+        }
+    }
+}
+
+impl Drop for InitGuard {
+    fn drop(&mut self) {
+        if self.is_complete {
+            return;
+        }
+        unsafe {
+            if let Some(device) = &self.device {
+                device.destroy_device(None);
+            }
+            if let Some(surface) = &mut self.surface {
+                surface.cleanup();
+            }
+            if let Some(debug) = &mut self.debug {
+                debug.cleanup();
+            }
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+/// Uniforms shared by every draw of a frame, bound as set 0, binding 0. Laid out for std140.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct GlobalUniformData {
+    _view_projection: uv::Mat4,
+    _view: uv::Mat4,
+    _projection: uv::Mat4,
+    _camera_position: align::Align16<uv::Vec3>
+}
+
+impl GlobalUniformData {
+    pub fn new(camera: &Camera) -> Self {
+        let view = camera.view();
+        let projection = camera.projection_matrix();
+        Self {
+            _view_projection: projection * view,
+            _view: view,
+            _projection: projection,
+            _camera_position: align::Align16(camera.position)
+        }
+    }
+}
+
 #[repr(C)]
 pub struct PushConstantData {
     _transform: uv::Mat2,
     _offset: uv::Vec2,
+    _depth: f32,
     _color: align::Align16<uv::Vec3>
 }
 
@@ -386,4 +1147,17 @@ impl PushConstantData {
     pub unsafe fn as_bytes(&self) -> &[u8] {
         any_as_u8_slice(self)
     }
+}
+
+#[repr(C)]
+pub struct PushConstantData3D {
+    _model: uv::Mat4,
+    _normal_matrix: [align::Align16<uv::Vec3>; 3],
+    _color: align::Align16<uv::Vec3>
+}
+
+impl PushConstantData3D {
+    pub unsafe fn as_bytes(&self) -> &[u8] {
+        any_as_u8_slice(self)
+    }
 }
\ No newline at end of file
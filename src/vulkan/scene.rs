@@ -0,0 +1,327 @@
+use super::game_object::{interpolated, reset_interpolation, Interpolate, Transform2DComponent, Transform3DComponent};
+
+use crate::ecs::entity::Entity;
+use crate::ecs::world::World;
+
+/// The entity this one is attached to. Its transforms are relative to the parent's, see `attach`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+/// The entities attached to this one, kept in sync with their `Parent` by `attach` and `set_parent`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Children(pub Vec<Entity>);
+
+/// The world space result of an entity's `Transform2DComponent` and those of its parents,
+/// written by `propagate_transforms`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlobalTransform2D {
+    /// Scale and rotation, parents can add shear by scaling a rotated child non-uniformly.
+    pub matrix: uv::Mat2,
+    pub translation: uv::Vec2,
+    /// The depths of the entity and its parents added up, clamped to [0, 1].
+    pub depth: f32,
+    local: Transform2DComponent,
+}
+
+impl GlobalTransform2D {
+    pub fn transform_point(&self, point: uv::Vec2) -> uv::Vec2 {
+        self.matrix * point + self.translation
+    }
+}
+
+/// The world space result of an entity's `Transform3DComponent` and those of its parents,
+/// written by `propagate_transforms`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlobalTransform3D {
+    pub matrix: uv::Mat4,
+    local: Transform3DComponent,
+}
+
+impl GlobalTransform3D {
+    pub fn translation(&self) -> uv::Vec3 {
+        self.matrix.extract_translation()
+    }
+
+    /// Inverse transpose of the upper 3x3, see `Transform3DComponent::normal_matrix`.
+    pub fn normal_matrix(&self) -> uv::Mat3 {
+        self.matrix.truncate().inversed().transposed()
+    }
+}
+
+/// A transform that is relative to the parent's transform of the same type.
+pub trait LocalTransform: Interpolate + PartialEq {
+    type Global: Copy + 'static;
+
+    /// Applies `self` after the parent's world transform, or on its own without a parent.
+    fn to_global(&self, parent: Option<&Self::Global>) -> Self::Global;
+
+    /// The local transform `global` was computed from.
+    fn source(global: &Self::Global) -> &Self;
+
+    /// The local transform that ends up at `global` under `parent`. `None` if the parent is
+    /// scaled to zero and can't be undone.
+    fn from_global(global: &Self::Global, parent: Option<&Self::Global>) -> Option<Self>;
+}
+
+impl LocalTransform for Transform2DComponent {
+    type Global = GlobalTransform2D;
+
+    fn to_global(&self, parent: Option<&GlobalTransform2D>) -> GlobalTransform2D {
+        match parent {
+            Some(parent) => GlobalTransform2D {
+                matrix: parent.matrix * self.mat2(),
+                translation: parent.transform_point(self.translation),
+                depth: (parent.depth + self.depth).clamp(0.0, 1.0),
+                local: *self
+            },
+            None => GlobalTransform2D {
+                matrix: self.mat2(),
+                translation: self.translation,
+                depth: self.depth,
+                local: *self
+            }
+        }
+    }
+
+    fn source(global: &GlobalTransform2D) -> &Self {
+        &global.local
+    }
+
+    fn from_global(global: &GlobalTransform2D, parent: Option<&GlobalTransform2D>) -> Option<Self> {
+        let (matrix, translation, depth) = match parent {
+            Some(parent) => {
+                if parent.matrix.determinant().abs() <= f32::EPSILON {
+                    return None;
+                }
+                let inverse = parent.matrix.inversed();
+                (inverse * global.matrix, inverse * (global.translation - parent.translation), global.depth - parent.depth)
+            },
+            None => (global.matrix, global.translation, global.depth)
+        };
+
+        // The columns are the rotated x and y axes, scaled. Shear has no place in the result and is lost.
+        let x_axis = matrix.cols[0];
+        let scale_x = x_axis.mag();
+        if scale_x <= f32::EPSILON {
+            return None;
+        }
+
+        Some(Self {
+            translation,
+            rotation: x_axis.y.atan2(x_axis.x),
+            scale: uv::Vec2::new(scale_x, matrix.determinant() / scale_x),
+            depth
+        })
+    }
+}
+
+impl LocalTransform for Transform3DComponent {
+    type Global = GlobalTransform3D;
+
+    fn to_global(&self, parent: Option<&GlobalTransform3D>) -> GlobalTransform3D {
+        let matrix = match parent {
+            Some(parent) => parent.matrix * self.mat4(),
+            None => self.mat4()
+        };
+        GlobalTransform3D { matrix, local: *self }
+    }
+
+    fn source(global: &GlobalTransform3D) -> &Self {
+        &global.local
+    }
+
+    fn from_global(global: &GlobalTransform3D, parent: Option<&GlobalTransform3D>) -> Option<Self> {
+        let matrix = match parent {
+            Some(parent) => {
+                if parent.matrix.determinant().abs() <= f32::EPSILON {
+                    return None;
+                }
+                parent.matrix.inversed() * global.matrix
+            },
+            None => global.matrix
+        };
+
+        // Scale is the length of each axis, a mirroring is put on x
+        let axes = matrix.truncate();
+        let mut scale = uv::Vec3::new(axes.cols[0].mag(), axes.cols[1].mag(), axes.cols[2].mag());
+        if scale.x <= f32::EPSILON || scale.y <= f32::EPSILON || scale.z <= f32::EPSILON {
+            return None;
+        }
+        if axes.determinant() < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        let rotation = uv::Mat3::new(axes.cols[0] / scale.x, axes.cols[1] / scale.y, axes.cols[2] / scale.z);
+        Some(Self {
+            translation: matrix.extract_translation(),
+            rotation: rotation.into_rotor3().normalized(),
+            scale
+        })
+    }
+}
+
+/// Attaches `child` to `parent`, moving it off any previous parent. The child's transforms are
+/// taken as relative to the parent from now on, so it moves along; use `set_parent` to keep it
+/// where it is instead.
+pub fn attach(world: &mut World, child: Entity, parent: Entity) {
+    assert!(world.is_alive(parent), "Cannot attach {:?} to {:?}, it is not alive!", child, parent);
+    assert!(!is_ancestor(world, child, parent), "Cannot attach {:?} to itself or one of its descendants!", child);
+
+    detach(world, child);
+    world.insert(child, Parent(parent));
+    match world.get_mut::<Children>(parent) {
+        Some(children) => children.0.push(child),
+        None => { world.insert(parent, Children(vec![child])); }
+    }
+
+    world.remove::<GlobalTransform2D>(child);
+    world.remove::<GlobalTransform3D>(child);
+    reset_interpolation(world, child);
+}
+
+/// Moves `child` to a new parent, or makes it a root with `None`, without moving it in the
+/// world: its local transforms are recomputed relative to the new parent.
+pub fn set_parent(world: &mut World, child: Entity, parent: Option<Entity>) {
+    let transform2d = relative_to::<Transform2DComponent>(world, child, parent);
+    let transform3d = relative_to::<Transform3DComponent>(world, child, parent);
+
+    match parent {
+        Some(parent) => attach(world, child, parent),
+        None => {
+            detach(world, child);
+            world.remove::<GlobalTransform2D>(child);
+            world.remove::<GlobalTransform3D>(child);
+            reset_interpolation(world, child);
+        }
+    }
+
+    if let Some(transform2d) = transform2d {
+        world.insert(child, transform2d);
+    }
+    if let Some(transform3d) = transform3d {
+        world.insert(child, transform3d);
+    }
+}
+
+/// The local `T` that keeps `child` in place under `parent`.
+fn relative_to<T: LocalTransform>(world: &World, child: Entity, parent: Option<Entity>) -> Option<T> {
+    let global = global_transform::<T>(world, child)?;
+    let parent_global = parent.and_then(|parent| global_transform::<T>(world, parent));
+    T::from_global(&global, parent_global.as_ref())
+}
+
+/// Removes `child` from its parent, leaving its local transforms as they are.
+fn detach(world: &mut World, child: Entity) {
+    let Some(Parent(parent)) = world.remove::<Parent>(child) else {
+        return;
+    };
+
+    if let Some(children) = world.get_mut::<Children>(parent) {
+        children.0.retain(|&other| other != child);
+        if children.0.is_empty() {
+            world.remove::<Children>(parent);
+        }
+    }
+}
+
+/// Takes the entity out of the hierarchy before it is despawned on its own. Its children become
+/// roots and stay where they are in the world.
+pub fn unlink(world: &mut World, entity: Entity) {
+    if let Some(Children(children)) = world.get::<Children>(entity).cloned() {
+        for child in children {
+            set_parent(world, child, None);
+        }
+    }
+    detach(world, entity);
+}
+
+/// Whether `ancestor` is `entity` itself or one of its parents.
+pub fn is_ancestor(world: &World, ancestor: Entity, entity: Entity) -> bool {
+    let mut current = entity;
+    loop {
+        if current == ancestor {
+            return true;
+        }
+        match world.get::<Parent>(current) {
+            Some(&Parent(parent)) => current = parent,
+            None => return false
+        }
+    }
+}
+
+/// Everything attached below `entity`, parents before their children.
+pub fn descendants(world: &World, entity: Entity) -> Vec<Entity> {
+    let mut descendants = vec![];
+    let mut stack = vec![entity];
+    while let Some(current) = stack.pop() {
+        if let Some(children) = world.get::<Children>(current) {
+            descendants.extend_from_slice(&children.0);
+            stack.extend_from_slice(&children.0);
+        }
+    }
+    descendants
+}
+
+/// Computes the current world transform of `entity` from its parents, without interpolation.
+/// The chain ends at the first parent that has no `T`.
+pub fn global_transform<T: LocalTransform>(world: &World, entity: Entity) -> Option<T::Global> {
+    let mut chain = vec![*world.get::<T>(entity)?];
+    let mut current = entity;
+    while let Some(&Parent(parent)) = world.get::<Parent>(current) {
+        match world.get::<T>(parent) {
+            Some(&local) => chain.push(local),
+            None => break
+        }
+        current = parent;
+    }
+
+    chain.iter().rev().fold(None, |parent, local| Some(local.to_global(parent.as_ref())))
+}
+
+/// Updates the `GlobalTransform2D` and `GlobalTransform3D` of every entity from its transforms,
+/// interpolated by `alpha` like `game_object::interpolated`. The renderer calls this before
+/// drawing each frame.
+pub fn propagate_transforms(world: &mut World, alpha: f32) {
+    propagate::<Transform2DComponent>(world, alpha);
+    propagate::<Transform3DComponent>(world, alpha);
+}
+
+/// Walks down from every root. An entity is dirty when its local transform differs from the one
+/// its global was computed from, and a dirty entity makes all of its descendants dirty too.
+/// Everything else keeps its global transform.
+fn propagate<T: LocalTransform>(world: &mut World, alpha: f32) {
+    let stale: Vec<Entity> = world.query::<T::Global>()
+        .map(|(entity, _)| entity)
+        .filter(|&entity| !world.has::<T>(entity))
+        .collect();
+    for entity in stale {
+        world.remove::<T::Global>(entity);
+    }
+
+    let roots: Vec<Entity> = world.query::<T>()
+        .map(|(entity, _)| entity)
+        .filter(|&entity| world.get::<Parent>(entity).is_none_or(|parent| !world.has::<T>(parent.0)))
+        .collect();
+
+    let mut stack: Vec<(Entity, Option<T::Global>, bool)> = roots.into_iter()
+        .map(|root| (root, None, false))
+        .collect();
+    while let Some((entity, parent, parent_dirty)) = stack.pop() {
+        let Some(local) = interpolated::<T>(world, entity, alpha) else {
+            continue;
+        };
+
+        let dirty = parent_dirty || world.get::<T::Global>(entity).is_none_or(|global| *T::source(global) != local);
+        let global = if dirty {
+            let global = local.to_global(parent.as_ref());
+            world.insert(entity, global);
+            global
+        } else {
+            *world.get::<T::Global>(entity).unwrap()
+        };
+
+        if let Some(children) = world.get::<Children>(entity) {
+            stack.extend(children.0.iter().map(|&child| (child, Some(global), dirty)));
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+use gpu_allocator::MemoryLocation;
+
+use super::buffer::Buffer;
+use super::command_pools::Pools;
+use super::error::ReverieError;
+
+pub struct Screenshot {}
+
+impl Screenshot {
+    /// Copies a color image into a host visible staging buffer and converts it to RGBA8.
+    /// The image is expected in `layout` and is left in that layout afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub fn read_image(
+        logical_device: &ash::Device,
+        allocator: &mut Allocator,
+        command_pool: vk::CommandPool,
+        queue: vk::Queue,
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> Result<image::RgbaImage, ReverieError> {
+        Self::is_bgra(format)?;
+
+        let mut staging_buffer = Self::create_staging_buffer(logical_device, allocator, extent)?;
+
+        let result = Pools::begin_single_time_commands(logical_device, command_pool).and_then(|command_buffer| {
+            Self::record_copy(logical_device, command_buffer, image, layout, staging_buffer.get_buffer(), extent);
+            Pools::end_single_time_commands(logical_device, command_pool, queue, command_buffer)
+        });
+
+        let image = result.and_then(|_| Self::to_rgba(&staging_buffer, format, extent));
+        staging_buffer.destroy(logical_device, allocator);
+        image
+    }
+
+    /// Whether the channels of `format` have to be swapped to get RGBA8, an error for formats
+    /// that can't be read back.
+    fn is_bgra(format: vk::Format) -> Result<bool, ReverieError> {
+        match format {
+            vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => Ok(false),
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => Ok(true),
+            _ => Err(ReverieError::Readback(format!("images with format {:?} are not supported", format)))
+        }
+    }
+
+    /// A buffer large enough for an RGBA8 image of `extent`, for `record_copy`.
+    pub fn create_staging_buffer(logical_device: &ash::Device, allocator: &mut Allocator, extent: vk::Extent2D) -> Result<Buffer, ReverieError> {
+        let size = extent.width as u64 * extent.height as u64 * 4;
+        Buffer::new(logical_device, allocator, size, vk::BufferUsageFlags::TRANSFER_DST, MemoryLocation::GpuToCpu, "Screenshot Staging Buffer")
+    }
+
+    /// Records copying `image` into `buffer`. The image is expected in `layout`, having just been
+    /// rendered to, and is left in that layout afterwards.
+    pub fn record_copy(
+        logical_device: &ash::Device,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        buffer: vk::Buffer,
+        extent: vk::Extent2D,
+    ) {
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .base_mip_level(0)
+            .level_count(1)
+            .base_array_layer(0)
+            .layer_count(1)
+            .build();
+
+        let to_transfer = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+            .old_layout(layout)
+            .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+
+        let to_original = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::TRANSFER_READ)
+            .dst_access_mask(vk::AccessFlags::empty())
+            .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .new_layout(layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+
+        let copy_region = vk::BufferImageCopy::builder()
+            .buffer_offset(0)
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1
+            })
+            .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+            .image_extent(vk::Extent3D { width: extent.width, height: extent.height, depth: 1 })
+            .build();
+
+        unsafe {
+            logical_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_transfer]
+            );
+            logical_device.cmd_copy_image_to_buffer(
+                command_buffer,
+                image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                buffer,
+                &[copy_region]
+            );
+            logical_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_original]
+            );
+        }
+    }
+
+    /// Converts the pixels `record_copy` wrote to `buffer` into an RGBA8 image. The copy has to
+    /// have finished executing.
+    pub fn to_rgba(buffer: &Buffer, format: vk::Format, extent: vk::Extent2D) -> Result<image::RgbaImage, ReverieError> {
+        let swizzle = Self::is_bgra(format)?;
+
+        let mut pixels = buffer
+            .mapped_slice()
+            .map(|slice| slice.to_vec())
+            .ok_or_else(|| ReverieError::Buffer("screenshot staging buffer is not host visible".into()))?;
+
+        if swizzle {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let image = image::RgbaImage::from_raw(extent.width, extent.height, pixels)
+            .ok_or_else(|| ReverieError::Readback("screenshot buffer does not match the image extent".into()))?;
+
+        Ok(image)
+    }
+
+    pub fn save_png<P: AsRef<std::path::Path>>(image: &image::RgbaImage, path: P) -> Result<(), ReverieError> {
+        image.save_with_format(path, image::ImageFormat::Png)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use ash::vk;
+use notify::Watcher;
+
+use super::error::ReverieError;
+
+/// Directory of the engine's own shaders, used to reload the built-in pipelines during development.
+pub const BUILTIN_SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/shaders");
+
+pub fn builtin_shader_path(file_name: &str) -> PathBuf {
+    Path::new(BUILTIN_SHADER_DIR).join(file_name)
+}
+
+/// Loads shaders from disk: `.spv` files are read as precompiled SPIR-V, everything else is
+/// compiled from GLSL with the stage taken from the extension (`.vert` or `.frag`).
+pub struct ShaderCompiler {
+    compiler: shaderc::Compiler,
+}
+
+impl ShaderCompiler {
+    pub fn new() -> Result<Self, ReverieError> {
+        let compiler = shaderc::Compiler::new()
+            .ok_or_else(|| ReverieError::Shader("failed to initialize the GLSL compiler".into()))?;
+
+        Ok(Self {
+            compiler
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u32>, ReverieError> {
+        let path = path.as_ref();
+
+        if path.extension().is_some_and(|extension| extension == "spv") {
+            return read_spirv(path);
+        }
+
+        let kind = shader_kind(path)?;
+        let source = std::fs::read_to_string(path)
+            .map_err(|err| ReverieError::Shader(format!("failed to read {}: {}", path.display(), err)))?;
+
+        let mut options = shaderc::CompileOptions::new()
+            .ok_or_else(|| ReverieError::Shader("failed to create GLSL compile options".into()))?;
+        options.set_generate_debug_info();
+
+        let artifact = self.compiler
+            .compile_into_spirv(&source, kind, &path.display().to_string(), "main", Some(&options))
+            .map_err(|err| ReverieError::Shader(format!("failed to compile {}: {}", path.display(), err)))?;
+
+        if artifact.get_num_warnings() > 0 {
+            println!("[Reverie][warn] {}", artifact.get_warning_messages());
+        }
+
+        Ok(artifact.as_binary().to_vec())
+    }
+}
+
+fn shader_kind(path: &Path) -> Result<shaderc::ShaderKind, ReverieError> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("vert") => Ok(shaderc::ShaderKind::Vertex),
+        Some("frag") => Ok(shaderc::ShaderKind::Fragment),
+        _ => Err(ReverieError::Shader(format!("cannot tell the shader stage of {}, use .vert, .frag or .spv", path.display())))
+    }
+}
+
+/// Reads a SPIR-V binary, checking its magic number and alignment.
+pub fn read_spirv<P: AsRef<Path>>(path: P) -> Result<Vec<u32>, ReverieError> {
+    let path = path.as_ref();
+    let mut file = std::fs::File::open(path)
+        .map_err(|err| ReverieError::Shader(format!("failed to open {}: {}", path.display(), err)))?;
+
+    ash::util::read_spv(&mut file)
+        .map_err(|err| ReverieError::Shader(format!("{} is not valid SPIR-V: {}", path.display(), err)))
+}
+
+/// Watches shader files and reports which of them changed since the last poll.
+///
+/// The parent directories are watched instead of the files themselves, since many editors save
+/// by replacing the file, which would end a watch on the file.
+pub struct ShaderWatcher {
+    watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    watched_files: HashSet<PathBuf>,
+    watched_dirs: HashSet<PathBuf>,
+}
+
+impl ShaderWatcher {
+    pub fn new() -> Result<Self, ReverieError> {
+        let (sender, events) = channel();
+        let watcher = notify::recommended_watcher(sender)?;
+
+        Ok(Self {
+            watcher,
+            events,
+            watched_files: HashSet::new(),
+            watched_dirs: HashSet::new()
+        })
+    }
+
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ReverieError> {
+        let path = path.as_ref().canonicalize()
+            .map_err(|err| ReverieError::Shader(format!("cannot watch {}: {}", path.as_ref().display(), err)))?;
+
+        if let Some(dir) = path.parent() {
+            if !self.watched_dirs.contains(dir) {
+                self.watcher.watch(dir, notify::RecursiveMode::NonRecursive)?;
+                self.watched_dirs.insert(dir.to_path_buf());
+            }
+        }
+        self.watched_files.insert(path);
+
+        Ok(())
+    }
+
+    /// Drains all pending file system events and returns the watched files that were written or replaced.
+    pub fn changed_files(&self) -> HashSet<PathBuf> {
+        let mut changed = HashSet::new();
+
+        for event in self.events.try_iter() {
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    println!("[Reverie][warn] Shader watcher error: {}", err);
+                    continue;
+                }
+            };
+
+            if !matches!(event.kind, notify::EventKind::Create(_) | notify::EventKind::Modify(_)) {
+                continue;
+            }
+
+            changed.extend(event.paths.into_iter().filter(|path| self.watched_files.contains(path)));
+        }
+
+        changed
+    }
+}
+
+/// Where the code of each stage came from, so it can be loaded again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderPaths {
+    pub vertex: PathBuf,
+    pub fragment: PathBuf,
+}
+
+impl ShaderPaths {
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(vertex: P, fragment: Q) -> Self {
+        Self {
+            vertex: vertex.as_ref().to_path_buf(),
+            fragment: fragment.as_ref().to_path_buf()
+        }
+    }
+
+    /// Whether any of the stages is one of `changed`, which holds canonical paths.
+    pub fn is_affected_by(&self, changed: &HashSet<PathBuf>) -> bool {
+        [&self.vertex, &self.fragment]
+            .iter()
+            .filter_map(|path| path.canonicalize().ok())
+            .any(|path| changed.contains(&path))
+    }
+
+    pub fn load(&self, compiler: &ShaderCompiler) -> Result<(Vec<u32>, Vec<u32>), ReverieError> {
+        Ok((compiler.load(&self.vertex)?, compiler.load(&self.fragment)?))
+    }
+}
+
+/// Wraps SPIR-V in a shader module, mapping failures to `ReverieError::Shader`.
+pub(crate) fn create_shader_module(logical_device: &ash::Device, code: &[u32], stage: &str) -> Result<vk::ShaderModule, ReverieError> {
+    let createinfo = vk::ShaderModuleCreateInfo::builder().code(code);
+    unsafe { logical_device.create_shader_module(&createinfo, None) }
+        .map_err(|result| ReverieError::from_vk_or(result, |result| ReverieError::Shader(format!("failed to create {} shader module ({})", stage, result))))
+}
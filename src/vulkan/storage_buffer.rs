@@ -0,0 +1,69 @@
+use std::marker::PhantomData;
+
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+use gpu_allocator::MemoryLocation;
+
+use super::buffer::Buffer;
+use super::error::ReverieError;
+
+/// A host visible array of up to `capacity` elements of `T`, bound as `STORAGE_BUFFER`.
+///
+/// `T` has to match the std430 layout of the shader's array element. Unlike uniform buffers
+/// the length is not fixed in the shader, so pass `len` along, e.g. in a push constant.
+pub struct StorageBuffer<T: Copy> {
+    buffer: Buffer,
+    capacity: usize,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> StorageBuffer<T> {
+    pub fn new(device: &ash::Device, allocator: &mut Allocator, capacity: usize, name: &str) -> Result<Self, ReverieError> {
+        assert!(capacity > 0, "Storage buffers need room for at least one element!");
+
+        let size = (capacity * std::mem::size_of::<T>()) as u64;
+        let buffer = Buffer::new(device, allocator, size, vk::BufferUsageFlags::STORAGE_BUFFER, MemoryLocation::CpuToGpu, name)?;
+
+        Ok(Self {
+            buffer,
+            capacity,
+            len: 0,
+            _marker: PhantomData
+        })
+    }
+
+    /// Replaces the contents with `data`.
+    pub fn write(&mut self, data: &[T]) -> Result<(), ReverieError> {
+        if data.len() > self.capacity {
+            return Err(ReverieError::Buffer(format!("{} elements do not fit into a storage buffer of {}", data.len(), self.capacity)));
+        }
+
+        let size = std::mem::size_of_val(data);
+        let bytes = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, size) };
+        self.buffer.mapped_slice_mut()
+            .ok_or_else(|| ReverieError::Buffer("storage buffer is not host visible".into()))?[..size]
+            .copy_from_slice(bytes);
+        self.len = data.len();
+
+        Ok(())
+    }
+
+    /// Covers the whole capacity, elements past `len` keep their old contents.
+    pub fn descriptor_info(&self) -> vk::DescriptorBufferInfo {
+        vk::DescriptorBufferInfo {
+            buffer: self.buffer.get_buffer(),
+            offset: 0,
+            range: vk::WHOLE_SIZE
+        }
+    }
+
+    pub fn get_buffer(&self) -> vk::Buffer { self.buffer.get_buffer() }
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    pub fn capacity(&self) -> usize { self.capacity }
+
+    pub fn destroy(&mut self, device: &ash::Device, allocator: &mut Allocator) {
+        self.buffer.destroy(device, allocator);
+    }
+}
@@ -1,7 +1,10 @@
 use ash::vk;
 
 use super::window::VulkanWindow;
+use super::error::ReverieError;
 
+/// Copies share the surface, only one of them may be cleaned up.
+#[derive(Clone)]
 pub struct VulkanSurface {
     pub surface: vk::SurfaceKHR,
     pub surface_loader: ash::extensions::khr::Surface
@@ -9,8 +12,9 @@ pub struct VulkanSurface {
 
 impl VulkanSurface {
     pub fn new(window: &VulkanWindow, entry: &ash::Entry, instance: &ash::Instance
-    ) -> Result<Self, vk::Result> {
-        let surface = unsafe { ash_window::create_surface(&entry, &instance, &window.window, None).unwrap() };
+    ) -> Result<Self, ReverieError> {
+        let surface = unsafe { ash_window::create_surface(entry, instance, &window.window, None) }
+            .map_err(ReverieError::Surface)?;
         let surface_loader = ash::extensions::khr::Surface::new(&entry, &instance);
 
         Ok(Self {
@@ -19,28 +23,20 @@ impl VulkanSurface {
         })
     }
 
-    pub fn get_capabilities(&self, physical_device: vk::PhysicalDevice) -> Result<vk::SurfaceCapabilitiesKHR, vk::Result> {
-        unsafe {
-            self.surface_loader.get_physical_device_surface_capabilities(physical_device, self.surface)
-        }
+    pub fn get_capabilities(&self, physical_device: vk::PhysicalDevice) -> Result<vk::SurfaceCapabilitiesKHR, ReverieError> {
+        Ok(unsafe { self.surface_loader.get_physical_device_surface_capabilities(physical_device, self.surface)? })
     }
 
-    pub fn get_present_modes(&self, physical_device: vk::PhysicalDevice) -> Result<Vec<vk::PresentModeKHR>, vk::Result> {
-        unsafe {
-            self.surface_loader.get_physical_device_surface_present_modes(physical_device, self.surface)
-        }
+    pub fn get_present_modes(&self, physical_device: vk::PhysicalDevice) -> Result<Vec<vk::PresentModeKHR>, ReverieError> {
+        Ok(unsafe { self.surface_loader.get_physical_device_surface_present_modes(physical_device, self.surface)? })
     }
 
-    pub fn get_formats(&self, physical_device: vk::PhysicalDevice) -> Result<Vec<vk::SurfaceFormatKHR>, vk::Result> {
-        unsafe {
-            self.surface_loader.get_physical_device_surface_formats(physical_device, self.surface)
-        }
+    pub fn get_formats(&self, physical_device: vk::PhysicalDevice) -> Result<Vec<vk::SurfaceFormatKHR>, ReverieError> {
+        Ok(unsafe { self.surface_loader.get_physical_device_surface_formats(physical_device, self.surface)? })
     }
 
-    pub fn get_physical_device_surface_support(&self, physical_device: vk::PhysicalDevice, queue_family_index: usize) -> Result<bool, vk::Result> {
-        unsafe {
-            self.surface_loader.get_physical_device_surface_support(physical_device, queue_family_index as u32, self.surface)
-        }
+    pub fn get_physical_device_surface_support(&self, physical_device: vk::PhysicalDevice, queue_family_index: usize) -> Result<bool, ReverieError> {
+        Ok(unsafe { self.surface_loader.get_physical_device_surface_support(physical_device, queue_family_index as u32, self.surface)? })
     }
 
     pub unsafe fn cleanup(&mut self) {
@@ -1,54 +1,196 @@
 use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+
 use super::surface::VulkanSurface;
+use super::image::Image;
 use super::queue::*;
+use super::output::{OutputEncoding, SCENE_FORMAT};
+use super::error::ReverieError;
+
+/// How the swapchain should present, each preference falls back to what the surface supports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapchainConfig {
+    /// Present modes in order of preference. `FIFO` is always available and used when none of them is.
+    pub present_modes: Vec<vk::PresentModeKHR>,
+    /// Formats and color spaces in order of preference. Without a match the surface's first format is used.
+    /// Anything but an `_SRGB` format gets an output pass that encodes the frame, see `OutputEncoding`.
+    pub surface_formats: Vec<vk::SurfaceFormatKHR>,
+    /// Wanted number of images, clamped to the limits of the surface.
+    pub image_count: u32,
+}
+
+impl Default for SwapchainConfig {
+    /// Vsync with triple buffering and 8 bit BGRA or RGBA, preferably sRGB encoded by the hardware.
+    fn default() -> Self {
+        Self {
+            present_modes: vec![vk::PresentModeKHR::FIFO],
+            surface_formats: SDR_SURFACE_FORMATS.to_vec(),
+            image_count: 3
+        }
+    }
+}
+
+const SDR_SURFACE_FORMATS: [vk::SurfaceFormatKHR; 4] = [
+    vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_SRGB, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR },
+    vk::SurfaceFormatKHR { format: vk::Format::R8G8B8A8_SRGB, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR },
+    vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_UNORM, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR },
+    vk::SurfaceFormatKHR { format: vk::Format::R8G8B8A8_UNORM, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR }
+];
+
+const HDR_SURFACE_FORMATS: [vk::SurfaceFormatKHR; 2] = [
+    vk::SurfaceFormatKHR { format: vk::Format::R16G16B16A16_SFLOAT, color_space: vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT },
+    vk::SurfaceFormatKHR { format: vk::Format::A2B10G10R10_UNORM_PACK32, color_space: vk::ColorSpaceKHR::HDR10_ST2084_EXT }
+];
+
+impl SwapchainConfig {
+    /// Without vsync, frames are presented as soon as they are done: `MAILBOX` replaces queued frames
+    /// without tearing, `IMMEDIATE` may tear. With vsync, `FIFO` waits for the vertical blank.
+    pub fn with_vsync(mut self, vsync: bool) -> Self {
+        self.present_modes = if vsync {
+            vec![vk::PresentModeKHR::FIFO]
+        } else {
+            vec![vk::PresentModeKHR::MAILBOX, vk::PresentModeKHR::IMMEDIATE, vk::PresentModeKHR::FIFO_RELAXED]
+        };
+        self
+    }
+
+    /// Prefers scRGB and then HDR10 surfaces, falling back to sRGB on displays without HDR.
+    /// The frame is tonemapped for the display, see `HdrSettings`.
+    pub fn with_hdr(mut self, hdr: bool) -> Self {
+        self.surface_formats = if hdr {
+            HDR_SURFACE_FORMATS.iter().chain(&SDR_SURFACE_FORMATS).copied().collect()
+        } else {
+            SDR_SURFACE_FORMATS.to_vec()
+        };
+        self
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        self.surface_formats.first().is_some_and(|&format| OutputEncoding::for_surface_format(format).is_hdr())
+    }
+
+    pub fn is_vsync(&self) -> bool {
+        self.present_modes.first().is_none_or(|&mode| mode == vk::PresentModeKHR::FIFO)
+    }
+
+    pub fn choose_present_mode(&self, available: &[vk::PresentModeKHR]) -> vk::PresentModeKHR {
+        self.present_modes
+            .iter()
+            .copied()
+            .find(|mode| available.contains(mode))
+            .unwrap_or(vk::PresentModeKHR::FIFO)
+    }
+
+    /// `None` only if the surface reports no formats at all.
+    pub fn choose_surface_format(&self, available: &[vk::SurfaceFormatKHR]) -> Option<vk::SurfaceFormatKHR> {
+        // A single undefined format means the surface takes anything
+        if let [vk::SurfaceFormatKHR { format: vk::Format::UNDEFINED, .. }] = available {
+            return self.surface_formats.first().copied().or(Some(available[0]));
+        }
+
+        self.surface_formats
+            .iter()
+            .copied()
+            .find(|preferred| available.contains(preferred))
+            .or_else(|| available.first().copied())
+    }
+
+    /// A `max_image_count` of 0 means there is no upper limit.
+    pub fn choose_image_count(&self, capabilities: &vk::SurfaceCapabilitiesKHR) -> u32 {
+        let image_count = self.image_count.max(capabilities.min_image_count);
+        match capabilities.max_image_count {
+            0 => image_count,
+            max_image_count => image_count.min(max_image_count)
+        }
+    }
+}
 
 pub struct VulkanSwapchain {
     pub swapchain_loader: ash::extensions::khr::Swapchain,
     pub swapchain: vk::SwapchainKHR,
     pub images: Vec<vk::Image>,
     pub imageviews: Vec<vk::ImageView>,
+    pub depth_images: Vec<Image>,
+    /// What the scene is drawn into when the surface needs an output pass, empty otherwise.
+    pub scene_images: Vec<Image>,
     pub framebuffers: Vec<vk::Framebuffer>,
+    /// The framebuffers of the output pass, which draws the scene images into the swapchain images.
+    pub output_framebuffers: Vec<vk::Framebuffer>,
+    /// One per image, signaled when rendering into the image finished. Presenting waits on it.
+    /// Frames in flight don't map to images, so these can't live in `FrameData`.
+    pub present_semaphores: Vec<vk::Semaphore>,
     pub surface_format: vk::SurfaceFormatKHR,
+    pub present_mode: vk::PresentModeKHR,
     pub extent: vk::Extent2D,
-    pub image_available: Vec<vk::Semaphore>,
-    pub rendering_finished: Vec<vk::Semaphore>,
-    pub may_begin_drawing: Vec<vk::Fence>,
     pub image_count: usize,
-    pub current_image: usize,
+    /// Whether the images can be copied from, see `VulkanRenderer::request_capture`.
+    pub supports_readback: bool,
 }
 
 impl VulkanSwapchain {
+    /// `old_swapchain` is the swapchain being replaced, or null. Passing it lets the driver reuse
+    /// its resources, it still has to be destroyed by the caller afterwards.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         instance: &ash::Instance,
         physical_device: vk::PhysicalDevice,
         logical_device: &ash::Device,
         surface: &VulkanSurface,
         queue_families: &QueueFamilies,
-    ) -> Result<VulkanSwapchain, vk::Result> {
+        config: &SwapchainConfig,
+        window_extent: vk::Extent2D,
+        old_swapchain: vk::SwapchainKHR,
+    ) -> Result<VulkanSwapchain, ReverieError> {
         let surface_capabilities = surface.get_capabilities(physical_device)?;
-        let extent = surface_capabilities.current_extent;
-        let surface_format = *surface.get_formats(physical_device)?.first().unwrap();
+        let extent = Self::choose_extent(&surface_capabilities, window_extent);
+        let surface_format = config.choose_surface_format(&surface.get_formats(physical_device)?)
+            .ok_or(ReverieError::Surface(vk::Result::ERROR_FORMAT_NOT_SUPPORTED))?;
+        let present_mode = config.choose_present_mode(&surface.get_present_modes(physical_device)?);
         let queuefamilies = [queue_families.graphics.unwrap()];
+        // Copying out of the swapchain images is only possible if the surface allows it
+        let supports_readback = surface_capabilities.supported_usage_flags.contains(vk::ImageUsageFlags::TRANSFER_SRC);
+        let image_usage = if supports_readback {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC
+        } else {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+        };
         let swapchain_create_info = vk::SwapchainCreateInfoKHR::builder()
             .surface(surface.surface)
-            .min_image_count(3
-                .max(surface_capabilities.min_image_count)
-                .min(surface_capabilities.max_image_count)
-            )
+            .min_image_count(config.choose_image_count(&surface_capabilities))
             .image_format(surface_format.format)
             .image_color_space(surface_format.color_space)
             .image_extent(extent)
             .image_array_layers(1)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+            .image_usage(image_usage)
             .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
             .queue_family_indices(&queuefamilies)
             .pre_transform(surface_capabilities.current_transform)
             .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
-            .present_mode(vk::PresentModeKHR::FIFO); //Sync with monitor refresh rate
+            .present_mode(present_mode)
+            .old_swapchain(old_swapchain);
         
         let swapchain_loader = ash::extensions::khr::Swapchain::new(instance, logical_device);
-        let swapchain = unsafe { swapchain_loader.create_swapchain(&swapchain_create_info, None)? };
-        let swapchain_images = unsafe { swapchain_loader.get_swapchain_images(swapchain)? };
+        let swapchain = unsafe { swapchain_loader.create_swapchain(&swapchain_create_info, None) }
+            .map_err(|result| ReverieError::from_vk_or(result, ReverieError::Swapchain))?;
+
+        // Destroys what was created so far if the swapchain can't be finished
+        let destroy = |imageviews: &[vk::ImageView], semaphores: &[vk::Semaphore]| unsafe {
+            for &semaphore in semaphores {
+                logical_device.destroy_semaphore(semaphore, None);
+            }
+            for &imageview in imageviews {
+                logical_device.destroy_image_view(imageview, None);
+            }
+            swapchain_loader.destroy_swapchain(swapchain, None);
+        };
+
+        let swapchain_images = match unsafe { swapchain_loader.get_swapchain_images(swapchain) } {
+            Ok(swapchain_images) => swapchain_images,
+            Err(err) => {
+                destroy(&[], &[]);
+                return Err(err.into());
+            }
+        };
         let image_count = swapchain_images.len();
         let mut swapchain_imageviews = Vec::with_capacity(swapchain_images.len());
         for image in &swapchain_images {
@@ -61,26 +203,27 @@ impl VulkanSwapchain {
             let imageview_create_info = vk::ImageViewCreateInfo::builder()
                 .image(*image)
                 .view_type(vk::ImageViewType::TYPE_2D)
-                .format(vk::Format::B8G8R8A8_UNORM)
+                .format(surface_format.format)
                 .subresource_range(*subresource_range);
-            let imageview = unsafe { 
-                logical_device.create_image_view(&imageview_create_info, None) 
-            }?;
-            swapchain_imageviews.push(imageview);
+            match unsafe { logical_device.create_image_view(&imageview_create_info, None) } {
+                Ok(imageview) => swapchain_imageviews.push(imageview),
+                Err(err) => {
+                    destroy(&swapchain_imageviews, &[]);
+                    return Err(err.into());
+                }
+            }
         }
 
-        let mut image_available = vec![];
-        let mut rendering_finished = vec![];
-        let mut may_begin_drawing = vec![];
         let semaphore_info = vk::SemaphoreCreateInfo::builder();
-        let fence_info = vk::FenceCreateInfo::builder().flags(vk::FenceCreateFlags::SIGNALED);
+        let mut present_semaphores = Vec::with_capacity(image_count);
         for _ in 0..image_count {
-            let semaphore_available = unsafe { logical_device.create_semaphore(&semaphore_info, None)? };
-            let semaphore_finished = unsafe { logical_device.create_semaphore(&semaphore_info, None)? };
-            image_available.push(semaphore_available);
-            rendering_finished.push(semaphore_finished);
-            let fence = unsafe { logical_device.create_fence(&fence_info, None)? };
-            may_begin_drawing.push(fence);
+            match unsafe { logical_device.create_semaphore(&semaphore_info, None) } {
+                Ok(semaphore) => present_semaphores.push(semaphore),
+                Err(err) => {
+                    destroy(&swapchain_imageviews, &present_semaphores);
+                    return Err(err.into());
+                }
+            }
         }
 
         Ok(VulkanSwapchain {
@@ -88,23 +231,90 @@ impl VulkanSwapchain {
             swapchain,
             images: swapchain_images,
             imageviews: swapchain_imageviews,
+            depth_images: vec![],
+            scene_images: vec![],
             framebuffers: vec![],
+            output_framebuffers: vec![],
+            present_semaphores,
             surface_format,
+            present_mode,
             extent,
             image_count,
-            current_image: 0,
-            image_available,
-            rendering_finished,
-            may_begin_drawing
+            supports_readback
         })
     }
 
-    pub fn create_framebuffers(&mut self, logical_device: &ash::Device, renderpass: vk::RenderPass) -> Result<(), vk::Result> {
+    /// The surface decides the extent, unless it reports `u32::MAX`, in which case the swapchain
+    /// follows the window within the limits of the surface.
+    pub fn choose_extent(capabilities: &vk::SurfaceCapabilitiesKHR, window_extent: vk::Extent2D) -> vk::Extent2D {
+        if capabilities.current_extent.width != u32::MAX {
+            return capabilities.current_extent;
+        }
+
+        vk::Extent2D {
+            width: window_extent.width.clamp(capabilities.min_image_extent.width, capabilities.max_image_extent.width),
+            height: window_extent.height.clamp(capabilities.min_image_extent.height, capabilities.max_image_extent.height)
+        }
+    }
+
+    pub fn create_depth_images(&mut self, logical_device: &ash::Device, allocator: &mut Allocator, depth_format: vk::Format) -> Result<(), ReverieError> {
+        for _ in 0..self.image_count {
+            let depth_image = Image::new_depth(logical_device, allocator, self.extent, depth_format)?;
+            self.depth_images.push(depth_image);
+        }
+
+        Ok(())
+    }
+
+    pub fn output_encoding(&self) -> OutputEncoding {
+        OutputEncoding::for_surface_format(self.surface_format)
+    }
+
+    /// The format the scene is drawn in, which differs from the surface format when there is an output pass.
+    pub fn scene_format(&self) -> vk::Format {
+        if self.output_encoding().needs_output_pass() {
+            SCENE_FORMAT
+        } else {
+            self.surface_format.format
+        }
+    }
+
+    /// Creates the scene images if the surface needs an output pass.
+    pub fn create_scene_images(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) -> Result<(), ReverieError> {
+        if !self.output_encoding().needs_output_pass() {
+            return Ok(());
+        }
+
+        for _ in 0..self.image_count {
+            let scene_image = Image::new(
+                logical_device,
+                allocator,
+                self.extent,
+                SCENE_FORMAT,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+                vk::ImageAspectFlags::COLOR,
+                "Scene Image"
+            )?;
+            self.scene_images.push(scene_image);
+        }
+
+        Ok(())
+    }
+
+    /// The framebuffers of the scene pass, drawing into the scene images if there are any and
+    /// directly into the swapchain images otherwise.
+    pub fn create_framebuffers(&mut self, logical_device: &ash::Device, renderpass: vk::RenderPass) -> Result<(), ReverieError> {
         let width = self.extent.width;
         let height = self.extent.height;
 
-        for iv in &self.imageviews {
-            let iview = [*iv];
+        let color_views: Vec<vk::ImageView> = if self.scene_images.is_empty() {
+            self.imageviews.clone()
+        } else {
+            self.scene_images.iter().map(|image| image.imageview).collect()
+        };
+
+        for (iv, depth_image) in color_views.iter().zip(&self.depth_images) {
+            let iview = [*iv, depth_image.imageview];
             let framebuffer_info = vk::FramebufferCreateInfo::builder()
                 .render_pass(renderpass)
                 .attachments(&iview)
@@ -118,23 +328,42 @@ impl VulkanSwapchain {
         Ok(())
     }
 
-    pub unsafe fn cleanup(&mut self, logical_device: &ash::Device) {
-        for fence in &self.may_begin_drawing {
-            logical_device.destroy_fence(*fence, None);
-        }
-        for semaphore in &self.image_available {
-            logical_device.destroy_semaphore(*semaphore, None);
+    pub fn create_output_framebuffers(&mut self, logical_device: &ash::Device, output_renderpass: vk::RenderPass) -> Result<(), ReverieError> {
+        for iv in &self.imageviews {
+            let iview = [*iv];
+            let framebuffer_info = vk::FramebufferCreateInfo::builder()
+                .render_pass(output_renderpass)
+                .attachments(&iview)
+                .width(self.extent.width)
+                .height(self.extent.height)
+                .layers(1);
+            let framebuffer = unsafe { logical_device.create_framebuffer(&framebuffer_info, None) }?;
+            self.output_framebuffers.push(framebuffer);
         }
-        for semaphore in &self.rendering_finished {
-            logical_device.destroy_semaphore(*semaphore, None);
+
+        Ok(())
+    }
+
+    /// Destroys everything that depends on the size of the swapchain images but keeps the
+    /// swapchain itself, so it can be handed to its replacement as `old_swapchain`.
+    pub(crate) unsafe fn destroy_attachments(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        for fb in self.framebuffers.drain(..).chain(self.output_framebuffers.drain(..)) {
+            logical_device.destroy_framebuffer(fb, None);
         }
-        for fb in &self.framebuffers {
-            logical_device.destroy_framebuffer(*fb, None);
+        for iv in self.imageviews.drain(..) {
+            logical_device.destroy_image_view(iv, None);
         }
-        for iv in &self.imageviews {
-            logical_device.destroy_image_view(*iv, None);
+        for mut image in self.depth_images.drain(..).chain(self.scene_images.drain(..)) {
+            image.destroy(logical_device, allocator);
         }
+    }
 
+    pub unsafe fn cleanup(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        self.destroy_attachments(logical_device, allocator);
+
+        for semaphore in self.present_semaphores.drain(..) {
+            logical_device.destroy_semaphore(semaphore, None);
+        }
         self.swapchain_loader.destroy_swapchain(self.swapchain, None);
     }
 }
\ No newline at end of file
@@ -0,0 +1,182 @@
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+
+use super::image::Image;
+use super::upload::Uploader;
+use super::descriptor::{layout_binding, DescriptorAllocator, DescriptorLayoutCache, DescriptorWriter};
+use super::error::ReverieError;
+
+/// Color textures are 8 bit sRGB RGBA, decoded to linear values when sampled.
+pub const TEXTURE_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+/// For textures that hold data rather than colors, like normal maps, sampled as stored.
+pub const LINEAR_TEXTURE_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+
+/// Refers to a texture owned by the renderer, see `VulkanRenderer::load_texture`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureHandle(pub(crate) usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// Used for magnification, minification and blending between mip levels.
+    pub filter: vk::Filter,
+    pub address_mode: vk::SamplerAddressMode,
+    /// Generates the full mip chain. Ignored when the device cannot blit the texture format with linear filtering.
+    pub mipmaps: bool,
+    /// Whether the pixels are sRGB colors, as image editors save them. Turn it off for data like normal maps.
+    pub srgb: bool,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            filter: vk::Filter::LINEAR,
+            address_mode: vk::SamplerAddressMode::REPEAT,
+            mipmaps: true,
+            srgb: true
+        }
+    }
+}
+
+impl TextureOptions {
+    /// Keeps texels sharp, for pixel art.
+    pub fn nearest() -> Self {
+        Self {
+            filter: vk::Filter::NEAREST,
+            ..Self::default()
+        }
+    }
+
+    pub fn format(&self) -> vk::Format {
+        if self.srgb { TEXTURE_FORMAT } else { LINEAR_TEXTURE_FORMAT }
+    }
+}
+
+/// Number of levels in a full mip chain, down to and including 1x1.
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// A sampled image together with its sampler and the descriptor set binding both.
+pub struct Texture {
+    pub image: Image,
+    pub sampler: vk::Sampler,
+    pub descriptor_set: vk::DescriptorSet,
+}
+
+impl Texture {
+    /// The layout of the texture descriptor sets: a single combined image sampler at binding 0,
+    /// read by the fragment shader.
+    pub fn descriptor_set_layout(layout_cache: &mut DescriptorLayoutCache, logical_device: &ash::Device) -> Result<vk::DescriptorSetLayout, ReverieError> {
+        layout_cache.get_or_create(logical_device, &[
+            layout_binding(0, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::ShaderStageFlags::FRAGMENT)
+        ])
+    }
+
+    /// Decodes a PNG or JPEG file into RGBA8 pixels.
+    pub fn decode_file<P: AsRef<std::path::Path>>(path: P) -> Result<image::RgbaImage, ReverieError> {
+        Ok(image::open(path)?.to_rgba8())
+    }
+
+    /// Records the upload of `pixels` into a new device local image. The image is not ready
+    /// for sampling until the uploader has been flushed.
+    ///
+    /// `linear_blit` tells whether the device supports linear blits of `options.format()`,
+    /// without it the texture only gets a single mip level.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut Allocator,
+        uploader: &mut Uploader,
+        descriptor_allocator: &mut DescriptorAllocator,
+        descriptor_set_layout: vk::DescriptorSetLayout,
+        pixels: &image::RgbaImage,
+        options: TextureOptions,
+        linear_blit: bool,
+    ) -> Result<Texture, ReverieError> {
+        let extent = vk::Extent2D { width: pixels.width(), height: pixels.height() };
+        if extent.width == 0 || extent.height == 0 {
+            return Err(ReverieError::Image(image::ImageError::Parameter(image::error::ParameterError::from_kind(
+                image::error::ParameterErrorKind::DimensionMismatch
+            ))));
+        }
+
+        let mip_levels = if options.mipmaps && linear_blit {
+            mip_level_count(extent.width, extent.height)
+        } else {
+            1
+        };
+
+        let mipmap_mode = match options.filter {
+            vk::Filter::NEAREST => vk::SamplerMipmapMode::NEAREST,
+            _ => vk::SamplerMipmapMode::LINEAR
+        };
+
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(options.filter)
+            .min_filter(options.filter)
+            .mipmap_mode(mipmap_mode)
+            .address_mode_u(options.address_mode)
+            .address_mode_v(options.address_mode)
+            .address_mode_w(options.address_mode)
+            .anisotropy_enable(false)
+            .border_color(vk::BorderColor::FLOAT_TRANSPARENT_BLACK)
+            .unnormalized_coordinates(false)
+            .compare_enable(false)
+            .min_lod(0.0)
+            .max_lod(mip_levels as f32);
+        let sampler = unsafe { logical_device.create_sampler(&sampler_info, None)? };
+
+        let image = match Image::with_mip_levels(
+            logical_device,
+            allocator,
+            extent,
+            options.format(),
+            vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+            vk::ImageAspectFlags::COLOR,
+            mip_levels,
+            "Texture"
+        ) {
+            Ok(image) => image,
+            Err(err) => {
+                unsafe { logical_device.destroy_sampler(sampler, None) };
+                return Err(err);
+            }
+        };
+
+        let mut texture = Texture {
+            image,
+            sampler,
+            descriptor_set: vk::DescriptorSet::null()
+        };
+
+        texture.descriptor_set = match descriptor_allocator.allocate(logical_device, descriptor_set_layout) {
+            Ok(descriptor_set) => descriptor_set,
+            Err(err) => {
+                texture.destroy(logical_device, allocator);
+                return Err(err);
+            }
+        };
+
+        // Recorded last, nothing can fail afterwards that would leave the upload pointing at a destroyed image
+        if let Err(err) = uploader.upload_image(logical_device, allocator, pixels.as_raw(), texture.image.image, extent, mip_levels) {
+            texture.destroy(logical_device, allocator);
+            return Err(err);
+        }
+
+        DescriptorWriter::new()
+            .write_image(0, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, vk::DescriptorImageInfo {
+                sampler,
+                image_view: texture.image.imageview,
+                image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+            })
+            .update(logical_device, texture.descriptor_set);
+
+        Ok(texture)
+    }
+
+    /// The descriptor set is freed together with the allocator it came from.
+    pub fn destroy(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        unsafe { logical_device.destroy_sampler(self.sampler, None) };
+        self.image.destroy(logical_device, allocator);
+    }
+}
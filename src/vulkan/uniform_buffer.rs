@@ -0,0 +1,51 @@
+use std::marker::PhantomData;
+
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+use gpu_allocator::MemoryLocation;
+
+use super::buffer::Buffer;
+use super::error::ReverieError;
+
+/// A host visible buffer holding a single `T`, bound as `UNIFORM_BUFFER`.
+///
+/// `T` has to match the std140 layout of the shader block, use `align::Align16` for vec3 members.
+/// The GPU reads the memory while the frame is in flight, so keep one buffer per frame in flight.
+pub struct UniformBuffer<T: Copy> {
+    buffer: Buffer,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> UniformBuffer<T> {
+    pub fn new(device: &ash::Device, allocator: &mut Allocator, name: &str) -> Result<Self, ReverieError> {
+        let size = std::mem::size_of::<T>() as u64;
+        let buffer = Buffer::new(device, allocator, size, vk::BufferUsageFlags::UNIFORM_BUFFER, MemoryLocation::CpuToGpu, name)?;
+
+        Ok(Self {
+            buffer,
+            _marker: PhantomData
+        })
+    }
+
+    pub fn write(&mut self, value: &T) -> Result<(), ReverieError> {
+        let bytes = unsafe { std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>()) };
+        self.buffer.mapped_slice_mut()
+            .ok_or_else(|| ReverieError::Buffer("uniform buffer is not host visible".into()))?
+            .copy_from_slice(bytes);
+        Ok(())
+    }
+
+    pub fn descriptor_info(&self) -> vk::DescriptorBufferInfo {
+        vk::DescriptorBufferInfo {
+            buffer: self.buffer.get_buffer(),
+            offset: 0,
+            range: std::mem::size_of::<T>() as u64
+        }
+    }
+
+    pub fn get_buffer(&self) -> vk::Buffer { self.buffer.get_buffer() }
+
+    pub fn destroy(&mut self, device: &ash::Device, allocator: &mut Allocator) {
+        self.buffer.destroy(device, allocator);
+    }
+}
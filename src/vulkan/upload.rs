@@ -0,0 +1,542 @@
+use ash::vk;
+use gpu_allocator::vulkan::Allocator;
+use gpu_allocator::MemoryLocation;
+
+use super::buffer::Buffer;
+use super::command_pools::Pools;
+use super::queue::{QueueFamilies, Queues};
+use super::error::ReverieError;
+
+/// A buffer copy recorded on the transfer queue that the graphics queue still has to take ownership of.
+struct PendingBuffer {
+    buffer: vk::Buffer,
+    dst_stage: vk::PipelineStageFlags,
+    dst_access: vk::AccessFlags,
+}
+
+/// An image whose first mip level was copied on the transfer queue. The graphics queue fills in the
+/// remaining levels with blits and moves everything to `SHADER_READ_ONLY_OPTIMAL`.
+struct PendingImage {
+    image: vk::Image,
+    extent: vk::Extent2D,
+    mip_levels: u32,
+}
+
+/// Copies data into device local (`GpuOnly`) buffers and images through host visible staging buffers.
+///
+/// Copies are batched into one transfer command buffer and submitted together by `flush`.
+/// When the transfer family differs from the graphics family the resources are released by the
+/// transfer queue and acquired by the graphics queue, ordered by a semaphore between the two submits.
+/// Images always need the graphics queue afterwards, since blits for mipmaps are graphics operations.
+pub struct Uploader {
+    transfer_family: u32,
+    graphics_family: u32,
+    transfer_command_buffer: vk::CommandBuffer,
+    graphics_command_buffer: vk::CommandBuffer,
+    transfer_finished: vk::Semaphore,
+    upload_finished: vk::Fence,
+    staging_buffers: Vec<Buffer>,
+    pending_buffers: Vec<PendingBuffer>,
+    pending_images: Vec<PendingImage>,
+    recording: bool,
+}
+
+impl Uploader {
+    pub fn new(logical_device: &ash::Device, pools: &Pools, queue_families: &QueueFamilies) -> Result<Uploader, ReverieError> {
+        let transfer_allocate_info = vk::CommandBufferAllocateInfo::builder()
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_pool(pools.transfer_command_pool)
+            .command_buffer_count(1);
+        let transfer_command_buffer = unsafe { logical_device.allocate_command_buffers(&transfer_allocate_info)? }[0];
+
+        let graphics_allocate_info = vk::CommandBufferAllocateInfo::builder()
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_pool(pools.graphics_command_pool)
+            .command_buffer_count(1);
+        let graphics_command_buffer = unsafe { logical_device.allocate_command_buffers(&graphics_allocate_info)? }[0];
+
+        let semaphore_info = vk::SemaphoreCreateInfo::builder();
+        let transfer_finished = unsafe { logical_device.create_semaphore(&semaphore_info, None)? };
+
+        let fence_info = vk::FenceCreateInfo::builder();
+        let upload_finished = unsafe { logical_device.create_fence(&fence_info, None)? };
+
+        Ok(Uploader {
+            transfer_family: queue_families.transfer.unwrap(),
+            graphics_family: queue_families.graphics.unwrap(),
+            transfer_command_buffer,
+            graphics_command_buffer,
+            transfer_finished,
+            upload_finished,
+            staging_buffers: vec![],
+            pending_buffers: vec![],
+            pending_images: vec![],
+            recording: false
+        })
+    }
+
+    fn needs_ownership_transfer(&self) -> bool {
+        self.transfer_family != self.graphics_family
+    }
+
+    /// Copies `data` into a new staging buffer and makes sure the transfer command buffer is recording.
+    fn stage<T: Copy>(&mut self, logical_device: &ash::Device, allocator: &mut Allocator, data: &[T]) -> Result<vk::Buffer, ReverieError> {
+        let size = std::mem::size_of_val(data) as u64;
+        let mut staging_buffer = Buffer::new(logical_device, allocator, size, vk::BufferUsageFlags::TRANSFER_SRC, MemoryLocation::CpuToGpu, "Staging Buffer")?;
+        let bytes = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, size as usize) };
+        match staging_buffer.mapped_slice_mut() {
+            Some(mapped) => mapped.copy_from_slice(bytes),
+            None => {
+                staging_buffer.destroy(logical_device, allocator);
+                return Err(ReverieError::Buffer("staging buffer is not host visible".into()));
+            }
+        }
+
+        if !self.recording {
+            let commandbuffer_begininfo = vk::CommandBufferBeginInfo::builder()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+            unsafe { logical_device.begin_command_buffer(self.transfer_command_buffer, &commandbuffer_begininfo)? };
+            self.recording = true;
+        }
+
+        let buffer = staging_buffer.get_buffer();
+        self.staging_buffers.push(staging_buffer);
+        Ok(buffer)
+    }
+
+    /// Records a copy of `data` into `dst`, which has to be created with `TRANSFER_DST` usage.
+    /// `dst_stage` and `dst_access` describe how the graphics queue reads the buffer afterwards.
+    /// Nothing is submitted until `flush` is called.
+    pub fn upload_buffer<T: Copy>(
+        &mut self,
+        logical_device: &ash::Device,
+        allocator: &mut Allocator,
+        data: &[T],
+        dst: vk::Buffer,
+        dst_stage: vk::PipelineStageFlags,
+        dst_access: vk::AccessFlags,
+    ) -> Result<(), ReverieError> {
+        let size = std::mem::size_of_val(data) as u64;
+        if size == 0 {
+            return Ok(());
+        }
+
+        let staging_buffer = self.stage(logical_device, allocator, data)?;
+
+        let copy_region = [vk::BufferCopy {
+            src_offset: 0,
+            dst_offset: 0,
+            size
+        }];
+
+        // Without an ownership transfer this is a plain memory barrier, otherwise it is the release half
+        let (src_family, dst_family, barrier_dst_stage, barrier_dst_access) = if self.needs_ownership_transfer() {
+            (self.transfer_family, self.graphics_family, vk::PipelineStageFlags::BOTTOM_OF_PIPE, vk::AccessFlags::empty())
+        } else {
+            (vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED, dst_stage, dst_access)
+        };
+        let barrier = vk::BufferMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .dst_access_mask(barrier_dst_access)
+            .src_queue_family_index(src_family)
+            .dst_queue_family_index(dst_family)
+            .buffer(dst)
+            .offset(0)
+            .size(vk::WHOLE_SIZE)
+            .build();
+
+        unsafe {
+            logical_device.cmd_copy_buffer(self.transfer_command_buffer, staging_buffer, dst, &copy_region);
+            logical_device.cmd_pipeline_barrier(
+                self.transfer_command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                barrier_dst_stage,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[barrier],
+                &[]
+            );
+        }
+
+        self.pending_buffers.push(PendingBuffer { buffer: dst, dst_stage, dst_access });
+
+        Ok(())
+    }
+
+    /// Records a copy of tightly packed `pixels` into the first mip level of a color image with
+    /// `TRANSFER_SRC | TRANSFER_DST | SAMPLED` usage. On `flush` the other mip levels are generated
+    /// with linear blits, so the format has to support linear filtering if `mip_levels` is above one.
+    /// Afterwards the whole image is in `SHADER_READ_ONLY_OPTIMAL`, ready for fragment shaders.
+    pub fn upload_image<T: Copy>(
+        &mut self,
+        logical_device: &ash::Device,
+        allocator: &mut Allocator,
+        pixels: &[T],
+        image: vk::Image,
+        extent: vk::Extent2D,
+        mip_levels: u32,
+    ) -> Result<(), ReverieError> {
+        let staging_buffer = self.stage(logical_device, allocator, pixels)?;
+
+        let all_levels = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .base_mip_level(0)
+            .level_count(mip_levels)
+            .base_array_layer(0)
+            .layer_count(1)
+            .build();
+
+        let to_transfer_dst = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::empty())
+            .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .old_layout(vk::ImageLayout::UNDEFINED)
+            .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(all_levels)
+            .build();
+
+        let copy_region = vk::BufferImageCopy::builder()
+            .buffer_offset(0)
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1
+            })
+            .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+            .image_extent(vk::Extent3D { width: extent.width, height: extent.height, depth: 1 })
+            .build();
+
+        unsafe {
+            logical_device.cmd_pipeline_barrier(
+                self.transfer_command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_transfer_dst]
+            );
+            logical_device.cmd_copy_buffer_to_image(
+                self.transfer_command_buffer,
+                staging_buffer,
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[copy_region]
+            );
+        }
+
+        // Release half of the ownership transfer, the layout stays the same until the graphics queue has it
+        if self.needs_ownership_transfer() {
+            let release = vk::ImageMemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::empty())
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .src_queue_family_index(self.transfer_family)
+                .dst_queue_family_index(self.graphics_family)
+                .image(image)
+                .subresource_range(all_levels)
+                .build();
+
+            unsafe {
+                logical_device.cmd_pipeline_barrier(
+                    self.transfer_command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[release]
+                );
+            }
+        }
+
+        self.pending_images.push(PendingImage { image, extent, mip_levels });
+
+        Ok(())
+    }
+
+    /// Records the graphics queue half of the upload: acquiring ownership and finishing the images.
+    /// Returns the stages that have to wait for the transfer submit.
+    fn record_graphics_commands(&self, logical_device: &ash::Device) -> Result<vk::PipelineStageFlags, ReverieError> {
+        let command_buffer = self.graphics_command_buffer;
+        let commandbuffer_begininfo = vk::CommandBufferBeginInfo::builder()
+            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+        unsafe { logical_device.begin_command_buffer(command_buffer, &commandbuffer_begininfo)? };
+
+        let mut wait_stage = if self.pending_images.is_empty() {
+            vk::PipelineStageFlags::empty()
+        } else {
+            vk::PipelineStageFlags::TRANSFER
+        };
+
+        if self.needs_ownership_transfer() {
+            let buffer_barriers: Vec<vk::BufferMemoryBarrier> = self.pending_buffers
+                .iter()
+                .map(|upload| vk::BufferMemoryBarrier::builder()
+                    .src_access_mask(vk::AccessFlags::empty())
+                    .dst_access_mask(upload.dst_access)
+                    .src_queue_family_index(self.transfer_family)
+                    .dst_queue_family_index(self.graphics_family)
+                    .buffer(upload.buffer)
+                    .offset(0)
+                    .size(vk::WHOLE_SIZE)
+                    .build())
+                .collect();
+            let image_barriers: Vec<vk::ImageMemoryBarrier> = self.pending_images
+                .iter()
+                .map(|upload| vk::ImageMemoryBarrier::builder()
+                    .src_access_mask(vk::AccessFlags::empty())
+                    .dst_access_mask(vk::AccessFlags::TRANSFER_READ | vk::AccessFlags::TRANSFER_WRITE)
+                    .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .src_queue_family_index(self.transfer_family)
+                    .dst_queue_family_index(self.graphics_family)
+                    .image(upload.image)
+                    .subresource_range(vk::ImageSubresourceRange {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        base_mip_level: 0,
+                        level_count: upload.mip_levels,
+                        base_array_layer: 0,
+                        layer_count: 1
+                    })
+                    .build())
+                .collect();
+
+            wait_stage = self.pending_buffers
+                .iter()
+                .fold(wait_stage, |stages, upload| stages | upload.dst_stage);
+
+            unsafe {
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    wait_stage,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &buffer_barriers,
+                    &image_barriers
+                );
+            }
+        }
+
+        for upload in &self.pending_images {
+            Self::record_mipmaps(logical_device, command_buffer, upload);
+        }
+
+        unsafe { logical_device.end_command_buffer(command_buffer)? };
+
+        Ok(wait_stage)
+    }
+
+    /// Blits every mip level from the one above it, leaving all levels in `SHADER_READ_ONLY_OPTIMAL`.
+    fn record_mipmaps(logical_device: &ash::Device, command_buffer: vk::CommandBuffer, upload: &PendingImage) {
+        let level_barrier = |level: u32, old_layout, new_layout, src_access, dst_access| {
+            vk::ImageMemoryBarrier::builder()
+                .src_access_mask(src_access)
+                .dst_access_mask(dst_access)
+                .old_layout(old_layout)
+                .new_layout(new_layout)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(upload.image)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: level,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1
+                })
+                .build()
+        };
+
+        let mut width = upload.extent.width as i32;
+        let mut height = upload.extent.height as i32;
+
+        for level in 1..upload.mip_levels {
+            let next_width = (width / 2).max(1);
+            let next_height = (height / 2).max(1);
+
+            let to_transfer_src = level_barrier(
+                level - 1,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                vk::AccessFlags::TRANSFER_WRITE,
+                vk::AccessFlags::TRANSFER_READ
+            );
+
+            let blit = vk::ImageBlit::builder()
+                .src_subresource(vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: level - 1,
+                    base_array_layer: 0,
+                    layer_count: 1
+                })
+                .src_offsets([vk::Offset3D { x: 0, y: 0, z: 0 }, vk::Offset3D { x: width, y: height, z: 1 }])
+                .dst_subresource(vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: level,
+                    base_array_layer: 0,
+                    layer_count: 1
+                })
+                .dst_offsets([vk::Offset3D { x: 0, y: 0, z: 0 }, vk::Offset3D { x: next_width, y: next_height, z: 1 }])
+                .build();
+
+            let to_shader_read = level_barrier(
+                level - 1,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                vk::AccessFlags::TRANSFER_READ,
+                vk::AccessFlags::SHADER_READ
+            );
+
+            unsafe {
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[to_transfer_src]
+                );
+                logical_device.cmd_blit_image(
+                    command_buffer,
+                    upload.image,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    upload.image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &[blit],
+                    vk::Filter::LINEAR
+                );
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[to_shader_read]
+                );
+            }
+
+            width = next_width;
+            height = next_height;
+        }
+
+        // The last level was only ever written to
+        let last_to_shader_read = level_barrier(
+            upload.mip_levels - 1,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::AccessFlags::TRANSFER_WRITE,
+            vk::AccessFlags::SHADER_READ
+        );
+
+        unsafe {
+            logical_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[last_to_shader_read]
+            );
+        }
+    }
+
+    /// Submits all recorded copies, waits for them to finish and frees the staging buffers.
+    pub fn flush(&mut self, logical_device: &ash::Device, allocator: &mut Allocator, queues: &Queues) -> Result<(), ReverieError> {
+        if !self.recording {
+            return Ok(());
+        }
+        self.recording = false;
+
+        unsafe { logical_device.end_command_buffer(self.transfer_command_buffer)? };
+
+        let transfer_command_buffers = [self.transfer_command_buffer];
+        let transfer_finished = [self.transfer_finished];
+
+        if self.needs_ownership_transfer() || !self.pending_images.is_empty() {
+            let wait_stage = self.record_graphics_commands(logical_device)?;
+
+            let transfer_submit = [vk::SubmitInfo::builder()
+                .command_buffers(&transfer_command_buffers)
+                .signal_semaphores(&transfer_finished)
+                .build()
+            ];
+
+            let graphics_command_buffers = [self.graphics_command_buffer];
+            let waiting_stages = [wait_stage];
+            let graphics_submit = [vk::SubmitInfo::builder()
+                .wait_semaphores(&transfer_finished)
+                .wait_dst_stage_mask(&waiting_stages)
+                .command_buffers(&graphics_command_buffers)
+                .build()
+            ];
+
+            unsafe {
+                logical_device.queue_submit(queues.transfer_queue, &transfer_submit, vk::Fence::null())?;
+                logical_device.queue_submit(queues.graphics_queue, &graphics_submit, self.upload_finished)?;
+            }
+        } else {
+            let transfer_submit = [vk::SubmitInfo::builder()
+                .command_buffers(&transfer_command_buffers)
+                .build()
+            ];
+
+            unsafe { logical_device.queue_submit(queues.transfer_queue, &transfer_submit, self.upload_finished)? };
+        }
+
+        unsafe {
+            logical_device.wait_for_fences(&[self.upload_finished], true, std::u64::MAX)?;
+            logical_device.reset_fences(&[self.upload_finished])?;
+        }
+
+        for staging_buffer in &mut self.staging_buffers {
+            staging_buffer.destroy(logical_device, allocator);
+        }
+        self.staging_buffers.clear();
+        self.pending_buffers.clear();
+        self.pending_images.clear();
+
+        Ok(())
+    }
+
+    /// Throws away everything recorded since the last `flush` without submitting it, for when a
+    /// resource a copy points at had to be destroyed. Uploads of other resources are lost as well.
+    pub fn discard(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        if self.recording {
+            // The pool allows resetting single command buffers, even while they are recording
+            if let Err(err) = unsafe { logical_device.reset_command_buffer(self.transfer_command_buffer, vk::CommandBufferResetFlags::empty()) } {
+                println!("[Reverie][error] Failed to reset the upload command buffer: {}", err);
+            }
+            self.recording = false;
+        }
+
+        for staging_buffer in &mut self.staging_buffers {
+            staging_buffer.destroy(logical_device, allocator);
+        }
+        self.staging_buffers.clear();
+        self.pending_buffers.clear();
+        self.pending_images.clear();
+    }
+
+    /// The command buffers are freed together with their pools.
+    pub fn destroy(&mut self, logical_device: &ash::Device, allocator: &mut Allocator) {
+        for staging_buffer in &mut self.staging_buffers {
+            staging_buffer.destroy(logical_device, allocator);
+        }
+        self.staging_buffers.clear();
+        unsafe {
+            logical_device.destroy_fence(self.upload_finished, None);
+            logical_device.destroy_semaphore(self.transfer_finished, None);
+        }
+    }
+}
@@ -2,71 +2,98 @@ use ash::vk;
 use gpu_allocator::vulkan::*;
 use gpu_allocator::MemoryLocation;
 
-use super::vertex::Vertex;
+use super::error::ReverieError;
 
 pub struct VertexBuffer {
     buffer: vk::Buffer,
     allocation: Allocation,
+    size: u64,
     vertex_count: u32,
 }
 
 impl VertexBuffer {
-    pub fn new(device: &ash::Device, allocator: &mut Allocator, size: u64) -> VertexBuffer {
+    pub fn new(device: &ash::Device, allocator: &mut Allocator, size: u64) -> Result<VertexBuffer, ReverieError> {
+        Self::create(device, allocator, size, vk::BufferUsageFlags::VERTEX_BUFFER, MemoryLocation::CpuToGpu)
+    }
+
+    /// Creates a buffer in device local memory. It can't be written through `update_buffer`,
+    /// fill it with `Uploader::upload_buffer` and `set_vertex_count` instead.
+    pub fn new_device_local(device: &ash::Device, allocator: &mut Allocator, size: u64) -> Result<VertexBuffer, ReverieError> {
+        Self::create(device, allocator, size, vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST, MemoryLocation::GpuOnly)
+    }
+
+    fn create(device: &ash::Device, allocator: &mut Allocator, size: u64, usage: vk::BufferUsageFlags, location: MemoryLocation) -> Result<VertexBuffer, ReverieError> {
         let vertex_buffer_create_info = vk::BufferCreateInfo::builder()
             .size(size)
-            .usage(vk::BufferUsageFlags::VERTEX_BUFFER)
+            .usage(usage)
             .sharing_mode(vk::SharingMode::EXCLUSIVE);
 
-        let vertex_buffer = unsafe {
-            device
-                .create_buffer(&vertex_buffer_create_info, None)
-                .expect("Failed to create Vertex Buffer")
-        };
+        let vertex_buffer = unsafe { device.create_buffer(&vertex_buffer_create_info, None)? };
 
         let mem_requirements = unsafe { device.get_buffer_memory_requirements(vertex_buffer) };
-        let location = MemoryLocation::CpuToGpu;
 
-        let allocation = allocator.allocate(&AllocationCreateDesc {
+        let allocation = match allocator.allocate(&AllocationCreateDesc {
             requirements: mem_requirements,
             location,
             linear: true,
             name: "Vertex Buffer"
-        }).expect("Failed to allocaate memory for vertex buffer!");
+        }) {
+            Ok(allocation) => allocation,
+            Err(err) => {
+                unsafe { device.destroy_buffer(vertex_buffer, None) };
+                return Err(err.into());
+            }
+        };
 
-        unsafe {
-            device
-                .bind_buffer_memory(vertex_buffer, allocation.memory(), allocation.offset())
-                .expect("Failed to bind vertex buffer");
+        if let Err(err) = unsafe { device.bind_buffer_memory(vertex_buffer, allocation.memory(), allocation.offset()) } {
+            if let Err(free_err) = allocator.free(allocation) {
+                println!("[Reverie][error] Failed to free vertex buffer memory: {}", free_err);
+            }
+            unsafe { device.destroy_buffer(vertex_buffer, None) };
+            return Err(err.into());
         }
 
-        VertexBuffer {
+        Ok(VertexBuffer {
             buffer: vertex_buffer,
             allocation,
+            size,
             vertex_count: 0
-        }
+        })
     }
 
     pub fn destroy(&mut self, device: &ash::Device, allocator: &mut Allocator) {
-        allocator
-            .free(std::mem::take(&mut self.allocation))
-            .expect("Failed to free vertex buffer memory!");
+        if let Err(err) = allocator.free(std::mem::take(&mut self.allocation)) {
+            println!("[Reverie][error] Failed to free vertex buffer memory: {}", err);
+        }
         unsafe {
             device.destroy_buffer(self.buffer, None);
         }
-        drop(self);
     }
 
-    pub fn get_vertex_buffer_size(count: usize) -> u64 {
-        (count * std::mem::size_of::<Vertex>()) as u64
+    pub fn get_vertex_buffer_size<V>(count: usize) -> u64 {
+        (count * std::mem::size_of::<V>()) as u64
     }
 
-    pub fn update_buffer(&mut self, data: &[Vertex]) {
-        let dst = self.allocation.mapped_ptr().unwrap().cast().as_ptr();
+    pub fn update_buffer<V: Copy>(&mut self, data: &[V]) -> Result<(), ReverieError> {
+        let data_size = std::mem::size_of_val(data) as u64;
+        if data_size > self.size {
+            return Err(ReverieError::Buffer(format!("vertex data needs {} bytes but the buffer only holds {}", data_size, self.size)));
+        }
+        let dst = self.allocation.mapped_ptr()
+            .ok_or_else(|| ReverieError::Buffer("vertex buffer is not host visible, use the Uploader for device local buffers".into()))?
+            .cast().as_ptr();
 
         unsafe {
             std::ptr::copy_nonoverlapping(data.as_ptr(), dst, data.len());
         }
         self.vertex_count = data.len() as u32;
+
+        Ok(())
+    }
+
+    /// Sets the number of vertices drawn after the contents were written by an upload.
+    pub fn set_vertex_count(&mut self, vertex_count: u32) {
+        self.vertex_count = vertex_count;
     }
 
     pub fn get_buffer(&self) -> vk::Buffer { self.buffer }
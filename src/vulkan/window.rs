@@ -1,7 +1,8 @@
+use ash::vk;
 use winit::event_loop::EventLoop;
 use winit::window::Window;
 
-use anyhow::Result;
+use super::error::ReverieError;
 
 pub struct VulkanWindow {
     pub window: Window,
@@ -10,13 +11,12 @@ pub struct VulkanWindow {
 }
 
 impl VulkanWindow {
-    pub fn create_window(title: &'static str, width: u32, height: u32) -> Result<(EventLoop<()>, Self)> {
+    pub fn create_window(title: &'static str, width: u32, height: u32) -> Result<(EventLoop<()>, Self), ReverieError> {
         let event_loop = EventLoop::new();
         let window = winit::window::WindowBuilder::new()
             .with_title(title)
             .with_inner_size(winit::dpi::LogicalSize::new(width, height))
-            .build(&event_loop)
-            .expect("Failed to create window.");
+            .build(&event_loop)?;
 
         Ok((event_loop, Self {
                 window,
@@ -24,4 +24,10 @@ impl VulkanWindow {
                 height
         }))
     }
+
+    /// The current size of the drawable area in physical pixels, zero while minimized on some platforms.
+    pub fn extent(&self) -> vk::Extent2D {
+        let size = self.window.inner_size();
+        vk::Extent2D { width: size.width, height: size.height }
+    }
 }
\ No newline at end of file
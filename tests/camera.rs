@@ -0,0 +1,101 @@
+use std::f32::consts::FRAC_PI_2;
+
+use ash::vk;
+use reverie::vulkan::camera::{Camera, FirstPersonController, OrbitController};
+
+fn assert_vec_eq(actual: uv::Vec3, expected: uv::Vec3) {
+    assert!(
+        (actual - expected).mag() < 1e-4,
+        "expected {:?}, got {:?}", expected, actual
+    );
+}
+
+fn project(camera: &Camera, point: uv::Vec3) -> uv::Vec3 {
+    let clip = camera.view_projection() * point.into_homogeneous_point();
+    clip.xyz() / clip.w
+}
+
+#[test]
+fn default_camera_maps_unit_square_to_clip_space_with_y_up() {
+    let camera = Camera::default();
+    assert_vec_eq(project(&camera, uv::Vec3::new(0.5, 0.5, 0.0)), uv::Vec3::new(0.5, -0.5, 0.5));
+}
+
+#[test]
+fn counter_clockwise_world_triangles_face_the_camera() {
+    let camera = Camera::default();
+    let triangle = [uv::Vec3::new(0.0, 0.0, 0.0), uv::Vec3::new(0.5, 0.0, 0.0), uv::Vec3::new(0.0, 0.5, 0.0)]
+        .map(|point| project(&camera, point));
+
+    // The signed area Vulkan uses to pick the front face, positive is counter-clockwise
+    let area: f32 = (0..3)
+        .map(|i| {
+            let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>() * -0.5;
+    assert!(area > 0.0, "expected a counter-clockwise triangle, got an area of {}", area);
+}
+
+#[test]
+fn aspect_ratio_widens_the_view_instead_of_stretching() {
+    let mut camera = Camera::default();
+    camera.set_aspect_ratio(vk::Extent2D { width: 800, height: 400 });
+
+    let projected = project(&camera, uv::Vec3::new(1.0, 1.0, 0.0));
+    assert!((projected.x - 0.5).abs() < 1e-5);
+    assert!((projected.y + 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn perspective_depth_grows_with_distance() {
+    let camera = Camera::perspective(FRAC_PI_2, 0.1, 100.0);
+
+    let near = project(&camera, uv::Vec3::new(0.0, 0.0, -1.0));
+    let far = project(&camera, uv::Vec3::new(0.0, 0.0, -50.0));
+    assert!(near.z > 0.0 && near.z < far.z && far.z < 1.0);
+}
+
+#[test]
+fn look_at_faces_target() {
+    let mut camera = Camera::perspective(FRAC_PI_2, 0.1, 100.0);
+    camera.position = uv::Vec3::new(0.0, 2.0, 5.0);
+
+    let target = uv::Vec3::new(3.0, 0.0, -1.0);
+    camera.look_at(target);
+
+    assert_vec_eq(camera.forward(), (target - camera.position).normalized());
+    assert!(camera.right().y.abs() < 1e-5, "look_at must not roll the camera");
+}
+
+#[test]
+fn first_person_turns_right_and_walks_forward() {
+    let mut camera = Camera::perspective(FRAC_PI_2, 0.1, 100.0);
+    let mut controller = FirstPersonController {
+        look_sensitivity: 1.0,
+        move_speed: 1.0,
+        ..Default::default()
+    };
+
+    controller.update(&mut camera, uv::Vec3::zero(), uv::Vec2::new(FRAC_PI_2, 0.0), 1.0);
+    assert_vec_eq(camera.forward(), uv::Vec3::unit_x());
+
+    controller.update(&mut camera, uv::Vec3::new(0.0, 0.0, 1.0), uv::Vec2::zero(), 2.0);
+    assert_vec_eq(camera.position, uv::Vec3::new(2.0, 0.0, 0.0));
+}
+
+#[test]
+fn orbit_keeps_distance_and_looks_at_target() {
+    let mut camera = Camera::perspective(FRAC_PI_2, 0.1, 100.0);
+    let mut controller = OrbitController {
+        target: uv::Vec3::new(1.0, 1.0, 1.0),
+        distance: 4.0,
+        ..Default::default()
+    };
+
+    controller.rotate(uv::Vec2::new(120.0, -60.0));
+    controller.update(&mut camera);
+
+    assert!(((camera.position - controller.target).mag() - 4.0).abs() < 1e-4);
+    assert_vec_eq(camera.forward(), (controller.target - camera.position).normalized());
+}
@@ -0,0 +1,53 @@
+use ash::vk;
+
+use reverie::utils::color::{linear_to_srgb, linear_to_srgb_rgb, srgb_to_linear, srgb_to_linear_rgb};
+use reverie::vulkan::output::OutputEncoding;
+
+#[test]
+fn black_and_white_are_unchanged() {
+    assert_eq!(srgb_to_linear(0.0), 0.0);
+    assert_eq!(linear_to_srgb(0.0), 0.0);
+    assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+    assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn mid_gray_is_darker_in_linear() {
+    assert!((srgb_to_linear(0.5) - 0.2140).abs() < 1e-4);
+    assert!((linear_to_srgb(0.2140) - 0.5).abs() < 1e-4);
+    // Below the linear segment threshold
+    assert!((srgb_to_linear(0.02) - 0.02 / 12.92).abs() < 1e-7);
+}
+
+#[test]
+fn conversions_round_trip() {
+    for i in 0..=255 {
+        let value = i as f32 / 255.0;
+        assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5, "{} does not round trip", value);
+    }
+
+    let color = uv::Vec3::new(0.1, 0.5, 0.9);
+    let round_trip = linear_to_srgb_rgb(srgb_to_linear_rgb(color));
+    assert!((round_trip - color).mag() < 1e-5);
+}
+
+#[test]
+fn output_encoding_follows_the_surface_format() {
+    let surface_format = |format, color_space| vk::SurfaceFormatKHR { format, color_space };
+
+    let srgb = OutputEncoding::for_surface_format(surface_format(vk::Format::B8G8R8A8_SRGB, vk::ColorSpaceKHR::SRGB_NONLINEAR));
+    assert_eq!(srgb, OutputEncoding::Srgb);
+    assert!(!srgb.needs_output_pass());
+
+    let unorm = OutputEncoding::for_surface_format(surface_format(vk::Format::B8G8R8A8_UNORM, vk::ColorSpaceKHR::SRGB_NONLINEAR));
+    assert_eq!(unorm, OutputEncoding::SrgbShader);
+    assert!(unorm.needs_output_pass());
+
+    let scrgb = OutputEncoding::for_surface_format(surface_format(vk::Format::R16G16B16A16_SFLOAT, vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT));
+    assert_eq!(scrgb, OutputEncoding::ScRgb);
+    assert!(scrgb.is_hdr() && scrgb.needs_output_pass());
+
+    let hdr10 = OutputEncoding::for_surface_format(surface_format(vk::Format::A2B10G10R10_UNORM_PACK32, vk::ColorSpaceKHR::HDR10_ST2084_EXT));
+    assert_eq!(hdr10, OutputEncoding::Hdr10);
+    assert!(hdr10.is_hdr());
+}
@@ -0,0 +1,122 @@
+//! Golden-image harness: renders scenes with a headless renderer and compares the result
+//! against the reference images in `tests/golden`.
+//!
+//! Run with `REVERIE_BLESS=1` to (re)record the references instead of comparing against them.
+//! On a mismatch the actual frame and a diff image are written next to the test binaries.
+
+use std::path::PathBuf;
+
+use reverie::vulkan::{renderer::VulkanRenderer, vertex::Vertex, mesh::Mesh};
+
+pub const WIDTH: u32 = 64;
+pub const HEIGHT: u32 = 64;
+
+/// Maximum difference allowed per color channel before a pixel counts as a mismatch.
+pub const TOLERANCE: u8 = 2;
+
+/// Renders a single frame of whatever `setup` adds to the renderer.
+pub fn render<F: FnOnce(&mut VulkanRenderer)>(setup: F) -> image::RgbaImage {
+    let mut renderer = VulkanRenderer::new_headless(WIDTH, HEIGHT)
+        .expect("Failed to create headless renderer");
+
+    setup(&mut renderer);
+
+    renderer.draw_frame().expect("Failed to draw frame");
+
+    renderer.capture_frame().expect("Failed to read back frame")
+}
+
+/// A unit square centered on the origin, spanning [-0.5, 0.5] on both axes.
+pub fn square_mesh(renderer: &mut VulkanRenderer) -> Mesh {
+    let mut mesh = Mesh::new(&renderer.device, &mut renderer.allocator, 4, 6)
+        .expect("Failed to create mesh");
+
+    let vertices: [Vertex; 4] = [
+        Vertex { pos: uv::Vec2::new(-0.5, -0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(0.0, 1.0) },
+        Vertex { pos: uv::Vec2::new(0.5, -0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(1.0, 1.0) },
+        Vertex { pos: uv::Vec2::new(0.5, 0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(1.0, 0.0) },
+        Vertex { pos: uv::Vec2::new(-0.5, 0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(0.0, 0.0) },
+    ];
+    let indices: [u32; 6] = [0, 1, 2, 2, 3, 0];
+
+    mesh.update_vertex_buffer(&vertices).expect("Failed to write vertices");
+    mesh.update_index_buffer(&indices).expect("Failed to write indices");
+    mesh
+}
+
+/// The same square as `square_mesh`, uploaded to device local memory through the transfer queue.
+pub fn static_square_mesh(renderer: &mut VulkanRenderer) -> Mesh {
+    let vertices: [Vertex; 4] = [
+        Vertex { pos: uv::Vec2::new(-0.5, -0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(0.0, 1.0) },
+        Vertex { pos: uv::Vec2::new(0.5, -0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(1.0, 1.0) },
+        Vertex { pos: uv::Vec2::new(0.5, 0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(1.0, 0.0) },
+        Vertex { pos: uv::Vec2::new(-0.5, 0.5), color: uv::Vec3::new(1.0, 1.0, 1.0), tex_coord: uv::Vec2::new(0.0, 0.0) },
+    ];
+    let indices: [u32; 6] = [0, 1, 2, 2, 3, 0];
+
+    renderer.create_static_mesh(&vertices, &indices)
+        .expect("Failed to upload mesh")
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.png", name))
+}
+
+fn output_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+/// Compares `actual` against the reference image `tests/golden/<name>.png`.
+pub fn assert_matches_golden(name: &str, actual: &image::RgbaImage) {
+    let reference_path = golden_path(name);
+
+    if std::env::var_os("REVERIE_BLESS").is_some() {
+        actual.save(&reference_path).expect("Failed to write reference image");
+        println!("Recorded reference image {}", reference_path.display());
+        return;
+    }
+
+    let reference = match image::open(&reference_path) {
+        Ok(reference) => reference.to_rgba8(),
+        Err(err) => panic!(
+            "Missing reference image {} ({}). Run with REVERIE_BLESS=1 to record it.",
+            reference_path.display(), err
+        )
+    };
+
+    assert_eq!(
+        reference.dimensions(), actual.dimensions(),
+        "Size of {} differs from the reference", name
+    );
+
+    let mut diff = image::RgbaImage::new(actual.width(), actual.height());
+    let mut mismatched = 0;
+    for ((expected, actual), diff) in reference.pixels().zip(actual.pixels()).zip(diff.pixels_mut()) {
+        let differs = expected.0.iter()
+            .zip(actual.0.iter())
+            .any(|(&e, &a)| e.abs_diff(a) > TOLERANCE);
+
+        *diff = if differs {
+            mismatched += 1;
+            image::Rgba([255, 0, 0, 255])
+        } else {
+            // Dimmed copy of the frame so the mismatches stand out
+            let [r, g, b, _] = actual.0;
+            image::Rgba([r / 4, g / 4, b / 4, 255])
+        };
+    }
+
+    if mismatched > 0 {
+        let dir = output_dir();
+        std::fs::create_dir_all(&dir).expect("Failed to create golden output directory");
+        let actual_path = dir.join(format!("{}.actual.png", name));
+        let diff_path = dir.join(format!("{}.diff.png", name));
+        actual.save(&actual_path).expect("Failed to write actual image");
+        diff.save(&diff_path).expect("Failed to write diff image");
+
+        panic!(
+            "{} pixels of {} differ from the reference by more than {}.\n  actual: {}\n  diff:   {}",
+            mismatched, name, TOLERANCE, actual_path.display(), diff_path.display()
+        );
+    }
+}
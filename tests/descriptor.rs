@@ -0,0 +1,21 @@
+use reverie::vulkan::descriptor::DescriptorAllocator;
+use reverie::vulkan::renderer::{GlobalUniformData, PushConstantData, PushConstantData3D};
+
+#[test]
+fn global_uniforms_match_std140_layout() {
+    // Three mat4 followed by a vec3 padded to 16 bytes
+    assert_eq!(std::mem::size_of::<GlobalUniformData>(), 3 * 64 + 16);
+}
+
+#[test]
+fn push_constants_fit_the_guaranteed_minimum() {
+    // Vulkan only guarantees 128 bytes of push constants
+    assert!(std::mem::size_of::<PushConstantData>() <= 128);
+    assert!(std::mem::size_of::<PushConstantData3D>() <= 128);
+}
+
+#[test]
+#[should_panic]
+fn descriptor_pools_need_room_for_a_set() {
+    DescriptorAllocator::new(0);
+}
@@ -0,0 +1,117 @@
+use reverie::ecs::entity::Entity;
+use reverie::ecs::storage::SparseSet;
+use reverie::ecs::system::Schedule;
+use reverie::ecs::world::World;
+use reverie::vulkan::game_object::{interpolated, save_previous, Transform2DComponent};
+
+#[derive(Debug, PartialEq)]
+struct Position(f32);
+
+#[derive(Debug, PartialEq)]
+struct Velocity(f32);
+
+#[test]
+fn sparse_set_keeps_lookups_valid_after_removal() {
+    let entities: Vec<Entity> = (0..3).map(|_| Entity::next()).collect();
+    let mut set = SparseSet::new();
+    for (i, &entity) in entities.iter().enumerate() {
+        set.insert(entity, i);
+    }
+
+    // The last component is moved into the gap
+    assert_eq!(set.remove(entities[0]), Some(0));
+    assert_eq!(set.remove(entities[0]), None);
+    assert_eq!(set.len(), 2);
+    assert_eq!(set.get(entities[1]), Some(&1));
+    assert_eq!(set.get(entities[2]), Some(&2));
+    assert_eq!(set.entities()[0], entities[2]);
+
+    assert_eq!(set.insert(entities[1], 10), Some(1));
+    assert_eq!(set.get(entities[1]), Some(&10));
+}
+
+#[test]
+fn despawn_removes_all_components() {
+    let mut world = World::new();
+    let a = world.spawn();
+    let b = world.spawn();
+    world.insert(a, Position(1.0));
+    world.insert(a, Velocity(2.0));
+    world.insert(b, Position(3.0));
+
+    assert!(world.despawn(a));
+    assert!(!world.despawn(a));
+    assert!(!world.is_alive(a));
+    assert!(!world.has::<Position>(a));
+    assert!(!world.has::<Velocity>(a));
+    assert_eq!(world.get::<Position>(b), Some(&Position(3.0)));
+    assert_eq!(world.len(), 1);
+
+    // Ids are never reused
+    let c = world.spawn();
+    assert!(c > b);
+    assert!(!world.has::<Position>(c));
+}
+
+#[test]
+fn query2_only_yields_entities_with_both_components() {
+    let mut world = World::new();
+    let moving = world.spawn();
+    let still = world.spawn();
+    world.insert(moving, Position(0.0));
+    world.insert(moving, Velocity(2.0));
+    world.insert(still, Position(5.0));
+
+    let matches: Vec<Entity> = world.query2::<Position, Velocity>().map(|(entity, _, _)| entity).collect();
+    assert_eq!(matches, vec![moving]);
+    assert_eq!(world.query::<Position>().count(), 2);
+
+    for (_, position, velocity) in world.query2_mut::<Position, Velocity>() {
+        position.0 += velocity.0;
+        velocity.0 = 0.0;
+    }
+    assert_eq!(world.get::<Position>(moving), Some(&Position(2.0)));
+    assert_eq!(world.get::<Velocity>(moving), Some(&Velocity(0.0)));
+    assert_eq!(world.get::<Position>(still), Some(&Position(5.0)));
+
+    assert_eq!(world.query2_mut::<Position, String>().count(), 0);
+}
+
+#[test]
+fn schedule_runs_systems_in_order() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, Position(1.0));
+
+    let mut schedule = Schedule::new()
+        .with_system(|world: &mut World, dt: f32| {
+            for (_, position) in world.query_mut::<Position>() {
+                position.0 += dt;
+            }
+        })
+        .with_system(|world: &mut World, _: f32| {
+            for (_, position) in world.query_mut::<Position>() {
+                position.0 *= 10.0;
+            }
+        });
+    schedule.run(&mut world, 0.5);
+
+    assert_eq!(schedule.len(), 2);
+    assert_eq!(world.get::<Position>(entity), Some(&Position(15.0)));
+}
+
+#[test]
+fn transforms_are_interpolated_from_the_previous_step() {
+    let mut world = World::new();
+    let entity = world.spawn();
+    world.insert(entity, Transform2DComponent::default());
+
+    // Drawn as is until a step has been saved
+    assert_eq!(interpolated::<Transform2DComponent>(&world, entity, 0.5), Some(Transform2DComponent::default()));
+
+    save_previous::<Transform2DComponent>(&mut world);
+    world.get_mut::<Transform2DComponent>(entity).unwrap().translation.x = 1.0;
+
+    let halfway = interpolated::<Transform2DComponent>(&world, entity, 0.5).unwrap();
+    assert_eq!(halfway.translation.x, 0.5);
+}
@@ -0,0 +1,30 @@
+use ash::vk;
+
+use reverie::vulkan::error::ReverieError;
+
+#[test]
+fn device_lost_and_out_of_memory_get_their_own_variants() {
+    assert!(matches!(ReverieError::from(vk::Result::ERROR_DEVICE_LOST), ReverieError::DeviceLost));
+    assert!(ReverieError::from(vk::Result::ERROR_OUT_OF_HOST_MEMORY).is_out_of_memory());
+    assert!(ReverieError::from(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY).is_out_of_memory());
+    assert!(ReverieError::from(gpu_allocator::AllocationError::OutOfMemory).is_out_of_memory());
+}
+
+#[test]
+fn swapchain_and_surface_results_are_sorted() {
+    assert!(matches!(
+        ReverieError::from(vk::Result::ERROR_OUT_OF_DATE_KHR),
+        ReverieError::Swapchain(vk::Result::ERROR_OUT_OF_DATE_KHR)
+    ));
+    assert!(matches!(
+        ReverieError::from(vk::Result::ERROR_SURFACE_LOST_KHR),
+        ReverieError::Surface(vk::Result::ERROR_SURFACE_LOST_KHR)
+    ));
+}
+
+#[test]
+fn other_results_stay_generic() {
+    let error = ReverieError::from(vk::Result::ERROR_INITIALIZATION_FAILED);
+    assert!(matches!(error, ReverieError::Vulkan(vk::Result::ERROR_INITIALIZATION_FAILED)));
+    assert!(!error.is_out_of_memory());
+}
@@ -0,0 +1,30 @@
+use ash::vk;
+
+use reverie::vulkan::renderer::VulkanRenderer;
+
+#[test]
+fn transient_buffers_outlive_the_frame_they_were_created_for() {
+    let mut renderer = VulkanRenderer::new_headless(64, 64)
+        .expect("Failed to create headless renderer");
+
+    // Created between frames, like from `App::render`
+    let frame_index = renderer.current_frame;
+    let buffer = renderer.create_transient_buffer(4, vk::BufferUsageFlags::VERTEX_BUFFER)
+        .expect("Failed to create transient buffer");
+    buffer.mapped_slice_mut().unwrap().copy_from_slice(&[1, 2, 3, 4]);
+    let handle = buffer.get_buffer();
+
+    renderer.draw_frame().expect("Failed to draw frame");
+
+    // The frame that was just submitted still owns the buffer, untouched
+    let frame = &renderer.frames[frame_index];
+    assert_eq!(frame.transient_buffers().len(), 1);
+    assert_eq!(frame.transient_buffers()[0].get_buffer(), handle);
+    assert_eq!(frame.transient_buffers()[0].mapped_slice().unwrap(), &[1, 2, 3, 4]);
+
+    // Freed once the frame comes around again
+    for _ in 0..renderer.frames_in_flight() {
+        renderer.draw_frame().expect("Failed to draw frame");
+    }
+    assert!(renderer.frames[frame_index].transient_buffers().is_empty());
+}
@@ -0,0 +1,273 @@
+mod common;
+
+use ash::vk;
+use reverie::vulkan::{game_object::{GameObject, Transform2DComponent}, pipeline::BlendMode, renderer::VulkanRenderer, texture::TextureOptions, vertex::Vertex};
+
+use common::*;
+
+#[test]
+fn clear_color() {
+    let frame = render(|_| {});
+    assert_matches_golden("clear_color", &frame);
+}
+
+#[test]
+fn single_square() {
+    let frame = render(|renderer| {
+        let mesh = square_mesh(renderer);
+        let mut square = GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0));
+        square.transform2d.translation.x = 0.25;
+        renderer.spawn(square);
+    });
+    assert_matches_golden("single_square", &frame);
+}
+
+#[test]
+fn static_mesh_matches_host_visible_mesh() {
+    let frame = render(|renderer| {
+        let mesh = static_square_mesh(renderer);
+        let mut square = GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0));
+        square.transform2d.translation.x = 0.25;
+        renderer.spawn(square);
+    });
+    assert_matches_golden("single_square", &frame);
+}
+
+#[test]
+fn failed_static_mesh_leaves_the_uploader_usable() {
+    let frame = render(|renderer| {
+        assert!(renderer.create_static_mesh::<Vertex>(&[], &[0, 1, 2]).is_err());
+
+        let mesh = static_square_mesh(renderer);
+        let mut square = GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0));
+        square.transform2d.translation.x = 0.25;
+        renderer.spawn(square);
+    });
+    assert_matches_golden("single_square", &frame);
+}
+
+#[test]
+fn overlapping_squares_keep_submission_order() {
+    let frame = render(|renderer| {
+        let mesh = square_mesh(renderer);
+        let mut back = GameObject::new(mesh, uv::Vec3::new(1.0, 0.0, 0.0));
+        back.transform2d.translation = uv::Vec2::new(-0.25, -0.25);
+        renderer.spawn(back);
+
+        let mesh = square_mesh(renderer);
+        let mut front = GameObject::new(mesh, uv::Vec3::new(0.0, 1.0, 0.0));
+        front.transform2d.translation = uv::Vec2::new(0.25, 0.25);
+        renderer.spawn(front);
+    });
+    assert_matches_golden("overlapping_squares", &frame);
+}
+
+#[test]
+fn depth_orders_squares_regardless_of_submission() {
+    let frame = render(|renderer| {
+        let mesh = square_mesh(renderer);
+        let mut front = GameObject::new(mesh, uv::Vec3::new(0.0, 1.0, 0.0));
+        front.transform2d.translation = uv::Vec2::new(0.25, 0.25);
+        front.transform2d.depth = 0.2;
+        renderer.spawn(front);
+
+        let mesh = square_mesh(renderer);
+        let mut back = GameObject::new(mesh, uv::Vec3::new(1.0, 0.0, 0.0));
+        back.transform2d.translation = uv::Vec2::new(-0.25, -0.25);
+        back.transform2d.depth = 0.6;
+        renderer.spawn(back);
+    });
+    assert_matches_golden("depth_layered_squares", &frame);
+}
+
+#[test]
+fn rotated_and_scaled_square() {
+    let frame = render(|renderer| {
+        let mesh = square_mesh(renderer);
+        let mut square = GameObject::new(mesh, uv::Vec3::new(1.0, 1.0, 0.0));
+        square.transform2d.scale = uv::Vec2::new(1.0, 0.5);
+        square.transform2d.rotation = std::f32::consts::FRAC_PI_2;
+        renderer.spawn(square);
+    });
+    assert_matches_golden("rotated_scaled_square", &frame);
+}
+
+#[test]
+fn frames_in_flight_show_the_latest_state() {
+    let mut renderer = VulkanRenderer::new_headless(WIDTH, HEIGHT)
+        .expect("Failed to create headless renderer");
+    renderer.set_frames_in_flight(3).expect("Failed to change frames in flight");
+
+    let mesh = square_mesh(&mut renderer);
+    let square = renderer.spawn(GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0)));
+
+    // Queue up more frames than there are frames in flight, moving the square each time
+    for step in 0..=5 {
+        renderer.world.get_mut::<Transform2DComponent>(square).unwrap().translation.x = step as f32 * 0.05;
+        renderer.draw_frame().expect("Failed to draw frame");
+    }
+
+    let frame = renderer.capture_frame().expect("Failed to read back frame");
+    assert_matches_golden("single_square", &frame);
+}
+
+#[test]
+fn textured_square() {
+    let frame = render(|renderer| {
+        // 2x2 checker, top row white and red, bottom row green and blue
+        let pixels = image::RgbaImage::from_raw(2, 2, vec![
+            255, 255, 255, 255,   255, 0, 0, 255,
+            0, 255, 0, 255,       0, 0, 255, 255,
+        ]).unwrap();
+        let texture = renderer.create_texture(&pixels, TextureOptions::nearest())
+            .expect("Failed to create texture");
+
+        let mesh = static_square_mesh(renderer);
+        renderer.spawn(GameObject::new_textured(mesh, texture));
+    });
+    assert_matches_golden("textured_square", &frame);
+}
+
+#[test]
+fn additive_blending_adds_overlapping_colors() {
+    let frame = render(|renderer| {
+        let additive = renderer.pipeline_builder_2d().blend_mode(BlendMode::Additive);
+        let pipeline = renderer.create_pipeline(additive).expect("Failed to create additive pipeline");
+
+        let mesh = square_mesh(renderer);
+        let mut red = GameObject::new(mesh, uv::Vec3::new(1.0, 0.0, 0.0));
+        red.transform2d.translation = uv::Vec2::new(-0.25, -0.25);
+        red.pipeline = Some(pipeline);
+        renderer.spawn(red);
+
+        let mesh = square_mesh(renderer);
+        let mut green = GameObject::new(mesh, uv::Vec3::new(0.0, 1.0, 0.0));
+        green.transform2d.translation = uv::Vec2::new(0.25, 0.25);
+        green.pipeline = Some(pipeline);
+        renderer.spawn(green);
+    });
+    assert_matches_golden("additive_squares", &frame);
+}
+
+#[test]
+fn object_colors_are_srgb() {
+    // Converted to linear for the shader and encoded again by the sRGB target, so the stored
+    // value matches the one set on the object
+    let frame = render(|renderer| {
+        let mesh = square_mesh(renderer);
+        renderer.spawn(GameObject::new(mesh, uv::Vec3::new(0.5, 0.5, 0.5)));
+    });
+    assert_matches_golden("mid_gray_square", &frame);
+}
+
+#[test]
+fn children_are_drawn_relative_to_their_parent() {
+    let frame = render(|renderer| {
+        let parent = renderer.world.spawn();
+        renderer.world.insert(parent, Transform2DComponent {
+            translation: uv::Vec2::new(0.25, 0.0),
+            ..Transform2DComponent::default()
+        });
+
+        let mesh = square_mesh(renderer);
+        let mut square = GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0));
+        square.parent = Some(parent);
+        renderer.spawn(square);
+    });
+    assert_matches_golden("single_square", &frame);
+}
+
+#[test]
+fn despawn_recursive_removes_children() {
+    let frame = render(|renderer| {
+        let mesh = square_mesh(renderer);
+        let tank = renderer.spawn(GameObject::new(mesh, uv::Vec3::new(1.0, 0.0, 0.0)));
+
+        let mesh = square_mesh(renderer);
+        let mut turret = GameObject::new(mesh, uv::Vec3::new(0.0, 1.0, 0.0));
+        turret.parent = Some(tank);
+        turret.transform2d.translation.x = 0.25;
+        renderer.spawn(turret);
+
+        assert_eq!(renderer.despawn_recursive(tank).expect("Failed to despawn"), 2);
+        assert!(renderer.world.is_empty());
+    });
+    assert_matches_golden("clear_color", &frame);
+}
+
+/// Pixels that aren't the black clear color.
+fn drawn_pixels(frame: &image::RgbaImage) -> usize {
+    frame.pixels().filter(|pixel| pixel[0] > 0 || pixel[1] > 0 || pixel[2] > 0).count()
+}
+
+/// Draws the blue unit square with `builder`, after checking the device can.
+fn render_square_with(builder: fn(&VulkanRenderer) -> reverie::vulkan::pipeline::PipelineBuilder) -> Option<image::RgbaImage> {
+    let mut supported = true;
+    let frame = render(|renderer| {
+        let builder = builder(renderer);
+        if builder.check_features(&renderer.enabled_features).is_err() {
+            supported = false;
+            return;
+        }
+        let pipeline = renderer.create_pipeline(builder).expect("Failed to create pipeline");
+
+        let mesh = square_mesh(renderer);
+        let mut square = GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0));
+        square.pipeline = Some(pipeline);
+        renderer.spawn(square);
+    });
+
+    if !supported {
+        println!("[Reverie][warn] Device lacks the features for this pipeline, skipping");
+        return None;
+    }
+    Some(frame)
+}
+
+#[test]
+fn wireframe_pipeline_draws_only_the_edges() {
+    let filled = render(|renderer| {
+        let mesh = square_mesh(renderer);
+        renderer.spawn(GameObject::new(mesh, uv::Vec3::new(0.0, 0.0, 1.0)));
+    });
+    let Some(wireframe) = render_square_with(|renderer| renderer.pipeline_builder_2d()
+        .polygon_mode(vk::PolygonMode::LINE)
+        .cull_mode(vk::CullModeFlags::NONE)
+    ) else {
+        return;
+    };
+
+    let center = (WIDTH / 2, HEIGHT / 2);
+    assert_eq!(filled.get_pixel(center.0, center.1)[2], 255);
+    assert_eq!(wireframe.get_pixel(center.0, center.1)[2], 0);
+    assert!(drawn_pixels(&wireframe) > 0);
+    assert!(drawn_pixels(&wireframe) < drawn_pixels(&filled) / 2);
+}
+
+#[test]
+fn line_list_pipeline_draws_lines() {
+    // The square's indices as lines: its bottom edge, a degenerate line and its left edge
+    let Some(frame) = render_square_with(|renderer| renderer.pipeline_builder_2d()
+        .topology(vk::PrimitiveTopology::LINE_LIST)
+        .cull_mode(vk::CullModeFlags::NONE)
+    ) else {
+        return;
+    };
+
+    assert_eq!(frame.get_pixel(WIDTH / 2, HEIGHT / 2)[2], 0);
+    assert!(drawn_pixels(&frame) > 0);
+    assert!(drawn_pixels(&frame) < (WIDTH * HEIGHT / 8) as usize);
+}
+
+#[test]
+fn point_list_pipeline_draws_a_pixel_per_vertex() {
+    let Some(frame) = render_square_with(|renderer| renderer.pipeline_builder_2d()
+        .topology(vk::PrimitiveTopology::POINT_LIST)
+        .cull_mode(vk::CullModeFlags::NONE)
+    ) else {
+        return;
+    };
+
+    // Four corners, each a single pixel wide
+    assert!((1..=4).contains(&drawn_pixels(&frame)), "{} pixels drawn", drawn_pixels(&frame));
+}
@@ -0,0 +1,58 @@
+use ash::vk;
+use reverie::vulkan::error::ReverieError;
+use reverie::vulkan::pipeline::{BlendMode, PipelineBuilder};
+
+#[test]
+fn opaque_blending_is_disabled() {
+    let state = BlendMode::Opaque.attachment_state();
+    assert_eq!(state.blend_enable, vk::FALSE);
+    assert_eq!(state.color_write_mask, vk::ColorComponentFlags::RGBA);
+}
+
+#[test]
+fn alpha_blending_mixes_with_the_destination() {
+    let state = BlendMode::Alpha.attachment_state();
+    assert_eq!(state.blend_enable, vk::TRUE);
+    assert_eq!(state.src_color_blend_factor, vk::BlendFactor::SRC_ALPHA);
+    assert_eq!(state.dst_color_blend_factor, vk::BlendFactor::ONE_MINUS_SRC_ALPHA);
+}
+
+#[test]
+fn additive_blending_keeps_the_destination() {
+    let state = BlendMode::Additive.attachment_state();
+    assert_eq!(state.blend_enable, vk::TRUE);
+    assert_eq!(state.dst_color_blend_factor, vk::BlendFactor::ONE);
+    assert_eq!(state.color_blend_op, vk::BlendOp::ADD);
+}
+
+#[test]
+fn non_solid_polygon_modes_need_fill_mode_non_solid() {
+    let enabled = vk::PhysicalDeviceFeatures { fill_mode_non_solid: vk::TRUE, ..Default::default() };
+
+    for mode in [vk::PolygonMode::LINE, vk::PolygonMode::POINT] {
+        let builder = PipelineBuilder::basic_2d().polygon_mode(mode);
+        assert!(matches!(builder.check_features(&vk::PhysicalDeviceFeatures::default()), Err(ReverieError::UnsupportedFeature(_))));
+        builder.check_features(&enabled).unwrap();
+    }
+}
+
+#[test]
+fn wide_lines_need_wide_lines() {
+    let enabled = vk::PhysicalDeviceFeatures { wide_lines: vk::TRUE, ..Default::default() };
+    let builder = PipelineBuilder::basic_2d()
+        .topology(vk::PrimitiveTopology::LINE_LIST)
+        .line_width(3.0);
+
+    assert!(matches!(builder.check_features(&vk::PhysicalDeviceFeatures::default()), Err(ReverieError::UnsupportedFeature(_))));
+    builder.check_features(&enabled).unwrap();
+}
+
+#[test]
+fn line_and_point_topologies_need_no_features() {
+    for topology in [vk::PrimitiveTopology::LINE_LIST, vk::PrimitiveTopology::LINE_STRIP, vk::PrimitiveTopology::POINT_LIST] {
+        PipelineBuilder::basic_2d()
+            .topology(topology)
+            .check_features(&vk::PhysicalDeviceFeatures::default())
+            .unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+use ash::vk;
+
+use reverie::vulkan::pipeline_cache::{file_name, is_compatible, unwrap_file, wrap_data};
+
+fn properties() -> vk::PhysicalDeviceProperties {
+    vk::PhysicalDeviceProperties {
+        vendor_id: 0x10de,
+        device_id: 0x2204,
+        driver_version: 0x0215_4000,
+        pipeline_cache_uuid: [7; vk::UUID_SIZE],
+        ..Default::default()
+    }
+}
+
+/// Cache data as a driver would return it: the Vulkan header followed by some payload.
+fn cache_data(properties: &vk::PhysicalDeviceProperties) -> Vec<u8> {
+    let mut data = vec![];
+    data.extend_from_slice(&32u32.to_ne_bytes());
+    data.extend_from_slice(&(vk::PipelineCacheHeaderVersion::ONE.as_raw() as u32).to_ne_bytes());
+    data.extend_from_slice(&properties.vendor_id.to_ne_bytes());
+    data.extend_from_slice(&properties.device_id.to_ne_bytes());
+    data.extend_from_slice(&properties.pipeline_cache_uuid);
+    data.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    data
+}
+
+#[test]
+fn saved_data_is_loaded_back() {
+    let properties = properties();
+    let data = cache_data(&properties);
+
+    let file = wrap_data(&data, &properties);
+    assert_eq!(unwrap_file(&file, &properties), Some(&data[..]));
+}
+
+#[test]
+fn caches_of_other_drivers_and_devices_are_ignored() {
+    let properties = properties();
+    let file = wrap_data(&cache_data(&properties), &properties);
+
+    let updated_driver = vk::PhysicalDeviceProperties { driver_version: properties.driver_version + 1, ..properties };
+    assert_eq!(unwrap_file(&file, &updated_driver), None);
+
+    let other_device = vk::PhysicalDeviceProperties { pipeline_cache_uuid: [8; vk::UUID_SIZE], ..properties };
+    assert_eq!(unwrap_file(&file, &other_device), None);
+    assert!(!is_compatible(&cache_data(&properties), &other_device));
+}
+
+#[test]
+fn corrupt_caches_are_ignored() {
+    let properties = properties();
+    let file = wrap_data(&cache_data(&properties), &properties);
+
+    assert_eq!(unwrap_file(&file[..file.len() - 1], &properties), None);
+    assert_eq!(unwrap_file(&file[..10], &properties), None);
+    assert_eq!(unwrap_file(&[], &properties), None);
+
+    let mut flipped = file.clone();
+    *flipped.last_mut().unwrap() ^= 0xff;
+    assert_eq!(unwrap_file(&flipped, &properties), None);
+
+    // Intact file, but the Vulkan header is too short
+    let short = wrap_data(&cache_data(&properties)[..20], &properties);
+    assert_eq!(unwrap_file(&short, &properties), None);
+}
+
+#[test]
+fn file_names_differ_per_driver_version() {
+    let properties = properties();
+    let updated_driver = vk::PhysicalDeviceProperties { driver_version: properties.driver_version + 1, ..properties };
+
+    assert_ne!(file_name(&properties), file_name(&updated_driver));
+}
@@ -0,0 +1,201 @@
+use ash::vk;
+
+use reverie::vulkan::error::ReverieError;
+use reverie::vulkan::reflection::{PipelineInterface, ReflectedBinding, ShaderInterface};
+use reverie::vulkan::renderer::{PushConstantData, PushConstantData3D};
+use reverie::vulkan::vertex::{Vertex, Vertex3D};
+
+fn globals() -> ReflectedBinding {
+    ReflectedBinding {
+        set: 0,
+        binding: 0,
+        name: "globals".into(),
+        descriptor_type: vk::DescriptorType::UNIFORM_BUFFER,
+        descriptor_count: 1
+    }
+}
+
+fn reflect(vertex: &[u32], fragment: &[u32]) -> PipelineInterface {
+    let vertex = ShaderInterface::reflect(vertex).expect("Failed to reflect vertex shader");
+    let fragment = ShaderInterface::reflect(fragment).expect("Failed to reflect fragment shader");
+    PipelineInterface::new(&vertex, &fragment).expect("Shader stages don't fit together")
+}
+
+/// The interface of the real `basic.vert` and `basic.frag`.
+fn basic_2d() -> PipelineInterface {
+    reflect(
+        vk_shader_macros::include_glsl!("./shaders/basic.vert", kind: vert),
+        vk_shader_macros::include_glsl!("./shaders/basic.frag", kind: frag)
+    )
+}
+
+fn basic_3d() -> PipelineInterface {
+    reflect(
+        vk_shader_macros::include_glsl!("./shaders/basic3d.vert", kind: vert),
+        vk_shader_macros::include_glsl!("./shaders/basic3d.frag", kind: frag)
+    )
+}
+
+fn textured_2d() -> PipelineInterface {
+    reflect(
+        vk_shader_macros::include_glsl!("./shaders/textured.vert", kind: vert),
+        vk_shader_macros::include_glsl!("./shaders/textured.frag", kind: frag)
+    )
+}
+
+/// `T` has to be exactly the push constant block, apart from the padding at its end that
+/// rounds it up to its alignment.
+fn assert_push_constants_match<T>(interface: &PipelineInterface) {
+    let size = std::mem::size_of::<T>() as u32;
+    let alignment = std::mem::align_of::<T>() as u32;
+
+    let ranges = interface.push_constant_ranges();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0].offset, 0);
+    assert_eq!(ranges[0].size.next_multiple_of(alignment), size,
+        "the push constant block is {} bytes, but {} is {} bytes", ranges[0].size, std::any::type_name::<T>(), size);
+
+    interface.validate_push_constants(&[vk::PushConstantRange {
+        stage_flags: vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+        offset: 0,
+        size
+    }]).unwrap();
+}
+
+#[test]
+fn vertex_attributes_match_the_2d_shaders() {
+    basic_2d().validate_vertex_layout(&Vertex::get_attribute_descriptions()).unwrap();
+    textured_2d().validate_vertex_layout(&Vertex::get_attribute_descriptions()).unwrap();
+}
+
+#[test]
+fn vertex_attributes_match_the_3d_shader() {
+    basic_3d().validate_vertex_layout(&Vertex3D::get_attribute_descriptions()).unwrap();
+}
+
+#[test]
+fn push_constant_data_matches_the_2d_shaders() {
+    assert_push_constants_match::<PushConstantData>(&basic_2d());
+    assert_push_constants_match::<PushConstantData>(&textured_2d());
+}
+
+#[test]
+fn push_constant_data_matches_the_3d_shader() {
+    assert_push_constants_match::<PushConstantData3D>(&basic_3d());
+}
+
+#[test]
+fn globals_are_bound_in_set_0() {
+    for interface in [basic_2d(), basic_3d(), textured_2d()] {
+        assert_eq!(interface.sets[0].len(), 1);
+        assert_eq!(interface.sets[0][0].binding, 0);
+        assert_eq!(interface.sets[0][0].descriptor_type, vk::DescriptorType::UNIFORM_BUFFER);
+    }
+}
+
+#[test]
+fn mismatched_attribute_formats_are_rejected() {
+    let mut attributes = Vertex::get_attribute_descriptions();
+    attributes[0].format = vk::Format::R32G32B32A32_SFLOAT;
+
+    let result = basic_2d().validate_vertex_layout(&attributes);
+    assert!(matches!(result, Err(ReverieError::InterfaceMismatch(_))));
+}
+
+#[test]
+fn missing_attributes_are_rejected() {
+    let attributes = &Vertex::get_attribute_descriptions()[..1];
+
+    let result = basic_2d().validate_vertex_layout(attributes);
+    assert!(matches!(result, Err(ReverieError::InterfaceMismatch(_))));
+}
+
+#[test]
+fn short_or_missing_push_constant_ranges_are_rejected() {
+    let interface = basic_2d();
+
+    let too_short = interface.validate_push_constants(&[vk::PushConstantRange {
+        stage_flags: vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+        offset: 0,
+        size: 32
+    }]);
+    assert!(matches!(too_short, Err(ReverieError::InterfaceMismatch(_))));
+
+    let vertex_only = interface.validate_push_constants(&[vk::PushConstantRange {
+        stage_flags: vk::ShaderStageFlags::VERTEX,
+        offset: 0,
+        size: 48
+    }]);
+    assert!(matches!(vertex_only, Err(ReverieError::InterfaceMismatch(_))));
+}
+
+#[test]
+fn derived_push_constants_cover_both_stages() {
+    let ranges = basic_2d().push_constant_ranges();
+
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0].stage_flags, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT);
+}
+
+#[test]
+fn derived_vertex_layout_is_tightly_packed() {
+    let (bindings, attributes) = basic_2d().packed_vertex_layout().unwrap();
+
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].stride, 8 + 12);
+    assert_eq!(attributes.iter().map(|attribute| attribute.offset).collect::<Vec<_>>(), vec![0, 8]);
+}
+
+#[test]
+fn bindings_are_merged_over_stages_and_sets_may_be_skipped() {
+    let vertex = ShaderInterface {
+        stage: vk::ShaderStageFlags::VERTEX,
+        inputs: vec![],
+        push_constants: None,
+        bindings: vec![ReflectedBinding { set: 1, ..globals() }]
+    };
+    let fragment = ShaderInterface {
+        stage: vk::ShaderStageFlags::FRAGMENT,
+        inputs: vec![],
+        push_constants: None,
+        bindings: vec![ReflectedBinding { set: 1, ..globals() }]
+    };
+    let interface = PipelineInterface::new(&vertex, &fragment).unwrap();
+
+    assert_eq!(interface.sets.len(), 2);
+    assert!(interface.sets[0].is_empty());
+    assert_eq!(interface.sets[1].len(), 1);
+    assert_eq!(interface.sets[1][0].stage_flags, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT);
+    assert!(interface.push_constant_ranges().is_empty());
+}
+
+#[test]
+fn set_layouts_are_checked_against_the_shaders() {
+    let interface = basic_2d();
+    let binding = |descriptor_type, stage_flags| vk::DescriptorSetLayoutBinding::builder()
+        .binding(0)
+        .descriptor_type(descriptor_type)
+        .descriptor_count(1)
+        .stage_flags(stage_flags)
+        .build();
+
+    // Wider stage flags than needed are fine
+    interface.validate_set_layout(0, &[
+        binding(vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+    ]).unwrap();
+
+    let wrong_type = interface.validate_set_layout(0, &[
+        binding(vk::DescriptorType::STORAGE_BUFFER, vk::ShaderStageFlags::VERTEX)
+    ]);
+    assert!(matches!(wrong_type, Err(ReverieError::InterfaceMismatch(_))));
+
+    let invisible = interface.validate_set_layout(0, &[
+        binding(vk::DescriptorType::UNIFORM_BUFFER, vk::ShaderStageFlags::FRAGMENT)
+    ]);
+    assert!(matches!(invisible, Err(ReverieError::InterfaceMismatch(_))));
+}
+
+#[test]
+fn invalid_spirv_is_rejected() {
+    assert!(matches!(ShaderInterface::reflect(&[0xdeadbeef, 0, 0]), Err(ReverieError::Shader(_))));
+}
@@ -0,0 +1,123 @@
+use std::f32::consts::FRAC_PI_2;
+
+use reverie::ecs::entity::Entity;
+use reverie::ecs::world::World;
+use reverie::vulkan::game_object::{Transform2DComponent, Transform3DComponent};
+use reverie::vulkan::scene::{
+    attach, descendants, global_transform, propagate_transforms, set_parent, unlink, Children, GlobalTransform2D, Parent
+};
+
+fn assert_vec_eq(actual: uv::Vec2, expected: uv::Vec2) {
+    assert!(
+        (actual - expected).mag() < 1e-5,
+        "expected {:?}, got {:?}", expected, actual
+    );
+}
+
+fn spawn_2d(world: &mut World, translation: uv::Vec2) -> Entity {
+    let entity = world.spawn();
+    world.insert(entity, Transform2DComponent { translation, ..Transform2DComponent::default() });
+    entity
+}
+
+#[test]
+fn children_move_with_their_parent() {
+    let mut world = World::new();
+    let tank = spawn_2d(&mut world, uv::Vec2::new(1.0, 0.0));
+    let turret = spawn_2d(&mut world, uv::Vec2::new(0.5, 0.0));
+    attach(&mut world, turret, tank);
+
+    propagate_transforms(&mut world, 1.0);
+    assert_vec_eq(world.get::<GlobalTransform2D>(turret).unwrap().translation, uv::Vec2::new(1.5, 0.0));
+
+    // Turning the tank swings the turret around it, the change reaches the unchanged child
+    world.get_mut::<Transform2DComponent>(tank).unwrap().rotation = FRAC_PI_2;
+    propagate_transforms(&mut world, 1.0);
+    assert_vec_eq(world.get::<GlobalTransform2D>(turret).unwrap().translation, uv::Vec2::new(1.0, 0.5));
+}
+
+#[test]
+fn reparenting_keeps_the_world_transform() {
+    let mut world = World::new();
+    let panel = world.spawn();
+    world.insert(panel, Transform2DComponent {
+        translation: uv::Vec2::new(2.0, 1.0),
+        rotation: FRAC_PI_2,
+        scale: uv::Vec2::new(2.0, 2.0),
+        depth: 0.5
+    });
+    let button = spawn_2d(&mut world, uv::Vec2::new(0.0, 3.0));
+    world.get_mut::<Transform2DComponent>(button).unwrap().depth = 0.25;
+
+    set_parent(&mut world, button, Some(panel));
+    assert_eq!(world.get::<Parent>(button), Some(&Parent(panel)));
+    assert_eq!(world.get::<Children>(panel), Some(&Children(vec![button])));
+
+    let global = global_transform::<Transform2DComponent>(&world, button).unwrap();
+    assert_vec_eq(global.translation, uv::Vec2::new(0.0, 3.0));
+    assert!((global.depth - 0.25).abs() < 1e-6);
+    assert_vec_eq(global.matrix.cols[0], uv::Vec2::new(1.0, 0.0));
+
+    let local = world.get::<Transform2DComponent>(button).unwrap();
+    assert_vec_eq(local.scale, uv::Vec2::new(0.5, 0.5));
+
+    set_parent(&mut world, button, None);
+    assert!(world.get::<Parent>(button).is_none());
+    assert!(world.get::<Children>(panel).is_none());
+    assert_vec_eq(world.get::<Transform2DComponent>(button).unwrap().translation, uv::Vec2::new(0.0, 3.0));
+}
+
+#[test]
+fn reparenting_keeps_the_world_transform_in_3d() {
+    let mut world = World::new();
+    let parent = world.spawn();
+    world.insert(parent, Transform3DComponent {
+        translation: uv::Vec3::new(1.0, 2.0, 3.0),
+        rotation: uv::Rotor3::from_rotation_xz(0.7),
+        scale: uv::Vec3::new(2.0, 2.0, 2.0)
+    });
+    let child = world.spawn();
+    let child_transform = Transform3DComponent {
+        translation: uv::Vec3::new(-1.0, 0.5, 4.0),
+        rotation: uv::Rotor3::from_rotation_xy(0.3),
+        scale: uv::Vec3::new(1.0, 3.0, 1.0)
+    };
+    world.insert(child, child_transform);
+
+    set_parent(&mut world, child, Some(parent));
+
+    let global = global_transform::<Transform3DComponent>(&world, child).unwrap();
+    let expected = child_transform.mat4();
+    for (actual, expected) in global.matrix.cols.iter().zip(expected.cols.iter()) {
+        assert!((*actual - *expected).mag() < 1e-4, "expected {:?}, got {:?}", expected, actual);
+    }
+}
+
+#[test]
+#[should_panic]
+fn cannot_attach_to_a_descendant() {
+    let mut world = World::new();
+    let parent = spawn_2d(&mut world, uv::Vec2::zero());
+    let child = spawn_2d(&mut world, uv::Vec2::zero());
+    attach(&mut world, child, parent);
+    attach(&mut world, parent, child);
+}
+
+#[test]
+fn unlinking_turns_children_into_roots() {
+    let mut world = World::new();
+    let root = spawn_2d(&mut world, uv::Vec2::new(1.0, 0.0));
+    let middle = spawn_2d(&mut world, uv::Vec2::new(1.0, 0.0));
+    let leaf = spawn_2d(&mut world, uv::Vec2::new(1.0, 0.0));
+    attach(&mut world, middle, root);
+    attach(&mut world, leaf, middle);
+    assert_eq!(descendants(&world, root), vec![middle, leaf]);
+
+    unlink(&mut world, middle);
+    world.despawn(middle);
+
+    assert!(descendants(&world, root).is_empty());
+    assert!(world.get::<Parent>(leaf).is_none());
+    propagate_transforms(&mut world, 1.0);
+    assert_vec_eq(world.get::<GlobalTransform2D>(leaf).unwrap().translation, uv::Vec2::new(3.0, 0.0));
+}
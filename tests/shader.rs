@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use reverie::vulkan::error::ReverieError;
+use reverie::vulkan::shader::{read_spirv, ShaderCompiler, ShaderPaths, ShaderWatcher};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("shader").join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const VERTEX_SHADER: &str = "#version 450\nvoid main() {\n    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);\n}\n";
+
+#[test]
+fn glsl_is_compiled_by_extension() {
+    let path = scratch_dir("compile").join("simple.vert");
+    std::fs::write(&path, VERTEX_SHADER).unwrap();
+
+    let compiler = ShaderCompiler::new().unwrap();
+    let code = compiler.load(&path).expect("Failed to compile shader");
+    assert_eq!(code[0], 0x0723_0203, "SPIR-V magic number");
+}
+
+#[test]
+fn compile_errors_are_reported_as_shader_errors() {
+    let path = scratch_dir("compile_error").join("broken.frag");
+    std::fs::write(&path, "#version 450\nvoid mian() {}\n").unwrap();
+
+    let compiler = ShaderCompiler::new().unwrap();
+    assert!(matches!(compiler.load(&path), Err(ReverieError::Shader(_))));
+}
+
+#[test]
+fn unknown_extensions_are_rejected() {
+    let path = scratch_dir("extension").join("shader.glsl");
+    std::fs::write(&path, VERTEX_SHADER).unwrap();
+
+    let compiler = ShaderCompiler::new().unwrap();
+    assert!(matches!(compiler.load(&path), Err(ReverieError::Shader(_))));
+}
+
+#[test]
+fn precompiled_spirv_is_read_as_is() {
+    let path = scratch_dir("spirv").join("shader.spv");
+    let words: [u32; 5] = [0x0723_0203, 0x0001_0000, 0, 1, 0];
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+    std::fs::write(&path, bytes).unwrap();
+
+    assert_eq!(read_spirv(&path).unwrap(), words);
+}
+
+#[test]
+fn invalid_spirv_is_rejected() {
+    let path = scratch_dir("invalid_spirv").join("shader.spv");
+    std::fs::write(&path, [1u8, 2, 3]).unwrap();
+
+    assert!(matches!(read_spirv(&path), Err(ReverieError::Shader(_))));
+}
+
+#[test]
+fn watcher_reports_changed_shaders() {
+    let dir = scratch_dir("watch");
+    let vertex = dir.join("watched.vert");
+    let fragment = dir.join("watched.frag");
+    std::fs::write(&vertex, VERTEX_SHADER).unwrap();
+    std::fs::write(&fragment, "").unwrap();
+
+    let mut watcher = ShaderWatcher::new().unwrap();
+    watcher.watch(&vertex).unwrap();
+    watcher.watch(&fragment).unwrap();
+    let paths = ShaderPaths::new(&vertex, &fragment);
+
+    std::fs::write(&vertex, VERTEX_SHADER.replace("0.0, 0.0", "0.5, 0.5")).unwrap();
+
+    // File system events arrive asynchronously
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut changed = watcher.changed_files();
+    while changed.is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+        changed = watcher.changed_files();
+    }
+
+    assert!(changed.contains(&vertex.canonicalize().unwrap()));
+    assert!(!changed.contains(&fragment.canonicalize().unwrap()));
+    assert!(paths.is_affected_by(&changed));
+}
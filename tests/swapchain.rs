@@ -0,0 +1,104 @@
+use ash::vk;
+
+use reverie::vulkan::swapchain::{SwapchainConfig, VulkanSwapchain};
+
+fn capabilities(current_extent: vk::Extent2D) -> vk::SurfaceCapabilitiesKHR {
+    vk::SurfaceCapabilitiesKHR {
+        min_image_count: 2,
+        max_image_count: 8,
+        current_extent,
+        min_image_extent: vk::Extent2D { width: 1, height: 1 },
+        max_image_extent: vk::Extent2D { width: 4096, height: 2048 },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn surface_extent_wins_when_set() {
+    let surface_extent = vk::Extent2D { width: 800, height: 600 };
+    let window_extent = vk::Extent2D { width: 1024, height: 768 };
+
+    assert_eq!(VulkanSwapchain::choose_extent(&capabilities(surface_extent), window_extent), surface_extent);
+}
+
+#[test]
+fn undefined_surface_extent_follows_the_window_within_limits() {
+    let undefined = vk::Extent2D { width: u32::MAX, height: u32::MAX };
+
+    let window_extent = vk::Extent2D { width: 1024, height: 768 };
+    assert_eq!(VulkanSwapchain::choose_extent(&capabilities(undefined), window_extent), window_extent);
+
+    let huge = vk::Extent2D { width: 10000, height: 0 };
+    assert_eq!(
+        VulkanSwapchain::choose_extent(&capabilities(undefined), huge),
+        vk::Extent2D { width: 4096, height: 1 }
+    );
+}
+
+#[test]
+fn image_count_respects_surface_limits() {
+    let config = SwapchainConfig::default();
+    let mut capabilities = capabilities(vk::Extent2D { width: 800, height: 600 });
+    assert_eq!(config.choose_image_count(&capabilities), 3);
+
+    capabilities.max_image_count = 2;
+    assert_eq!(config.choose_image_count(&capabilities), 2);
+
+    // No upper limit
+    capabilities.min_image_count = 4;
+    capabilities.max_image_count = 0;
+    assert_eq!(config.choose_image_count(&capabilities), 4);
+}
+
+#[test]
+fn present_mode_falls_back_to_fifo() {
+    let config = SwapchainConfig::default().with_vsync(false);
+    assert!(!config.is_vsync());
+
+    let all = [vk::PresentModeKHR::FIFO, vk::PresentModeKHR::IMMEDIATE, vk::PresentModeKHR::MAILBOX];
+    assert_eq!(config.choose_present_mode(&all), vk::PresentModeKHR::MAILBOX);
+
+    let no_mailbox = [vk::PresentModeKHR::FIFO, vk::PresentModeKHR::IMMEDIATE];
+    assert_eq!(config.choose_present_mode(&no_mailbox), vk::PresentModeKHR::IMMEDIATE);
+
+    assert_eq!(config.choose_present_mode(&[vk::PresentModeKHR::FIFO]), vk::PresentModeKHR::FIFO);
+
+    let vsync = config.with_vsync(true);
+    assert!(vsync.is_vsync());
+    assert_eq!(vsync.choose_present_mode(&all), vk::PresentModeKHR::FIFO);
+}
+
+#[test]
+fn surface_format_prefers_the_configured_formats() {
+    let config = SwapchainConfig::default();
+    let rgba = vk::SurfaceFormatKHR { format: vk::Format::R8G8B8A8_UNORM, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR };
+    let bgra_srgb = vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_SRGB, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR };
+    let hdr = vk::SurfaceFormatKHR { format: vk::Format::A2B10G10R10_UNORM_PACK32, color_space: vk::ColorSpaceKHR::HDR10_ST2084_EXT };
+
+    assert_eq!(config.choose_surface_format(&[hdr, rgba]), Some(rgba));
+    // sRGB formats win regardless of the order the surface lists them in
+    assert_eq!(config.choose_surface_format(&[rgba, bgra_srgb]), Some(bgra_srgb));
+    // Nothing preferred is available, take what the surface offers first
+    assert_eq!(config.choose_surface_format(&[hdr]), Some(hdr));
+    assert_eq!(config.choose_surface_format(&[]), None);
+
+    let anything = vk::SurfaceFormatKHR { format: vk::Format::UNDEFINED, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR };
+    assert_eq!(config.choose_surface_format(&[anything]), Some(config.surface_formats[0]));
+}
+
+#[test]
+fn hdr_surface_formats_fall_back_to_srgb() {
+    let config = SwapchainConfig::default().with_hdr(true);
+    assert!(config.is_hdr());
+    assert!(!SwapchainConfig::default().is_hdr());
+
+    let srgb = vk::SurfaceFormatKHR { format: vk::Format::B8G8R8A8_SRGB, color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR };
+    let hdr10 = vk::SurfaceFormatKHR { format: vk::Format::A2B10G10R10_UNORM_PACK32, color_space: vk::ColorSpaceKHR::HDR10_ST2084_EXT };
+    let scrgb = vk::SurfaceFormatKHR { format: vk::Format::R16G16B16A16_SFLOAT, color_space: vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT };
+
+    assert_eq!(config.choose_surface_format(&[srgb, hdr10, scrgb]), Some(scrgb));
+    assert_eq!(config.choose_surface_format(&[srgb, hdr10]), Some(hdr10));
+    assert_eq!(config.choose_surface_format(&[srgb]), Some(srgb));
+
+    assert_eq!(config.with_hdr(false), SwapchainConfig::default());
+}
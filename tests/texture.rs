@@ -0,0 +1,31 @@
+use reverie::vulkan::texture::{mip_level_count, TextureOptions, LINEAR_TEXTURE_FORMAT, TEXTURE_FORMAT};
+
+#[test]
+fn mip_chain_goes_down_to_one_texel() {
+    assert_eq!(mip_level_count(1, 1), 1);
+    assert_eq!(mip_level_count(2, 2), 2);
+    assert_eq!(mip_level_count(256, 256), 9);
+    assert_eq!(mip_level_count(512, 512), 10);
+}
+
+#[test]
+fn mip_chain_follows_the_larger_side() {
+    assert_eq!(mip_level_count(256, 128), 9);
+    assert_eq!(mip_level_count(1, 300), 9);
+    assert_eq!(mip_level_count(300, 1), 9);
+}
+
+#[test]
+fn default_options_filter_linearly_with_mipmaps() {
+    let options = TextureOptions::default();
+    assert_eq!(options.filter, ash::vk::Filter::LINEAR);
+    assert!(options.mipmaps);
+    assert_eq!(TextureOptions::nearest().filter, ash::vk::Filter::NEAREST);
+}
+
+#[test]
+fn textures_are_srgb_unless_they_hold_data() {
+    assert_eq!(TextureOptions::default().format(), TEXTURE_FORMAT);
+    let normal_map = TextureOptions { srgb: false, ..TextureOptions::default() };
+    assert_eq!(normal_map.format(), LINEAR_TEXTURE_FORMAT);
+}
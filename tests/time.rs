@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use reverie::time::FixedTimestep;
+
+const STEP: Duration = Duration::from_millis(10);
+
+#[test]
+fn frame_time_is_spent_in_whole_steps() {
+    let mut timestep = FixedTimestep::new(STEP, 8);
+
+    assert_eq!(timestep.advance(Duration::from_millis(25)), 2);
+    assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+
+    // The leftover half step completes with the next frame
+    assert_eq!(timestep.advance(Duration::from_millis(5)), 1);
+    assert_eq!(timestep.alpha(), 0.0);
+
+    assert_eq!(timestep.advance(Duration::ZERO), 0);
+}
+
+#[test]
+fn frame_rate_does_not_change_the_number_of_steps() {
+    let mut fast = FixedTimestep::new(STEP, 8);
+    let mut slow = FixedTimestep::new(STEP, 8);
+
+    // One second at 250 and at 25 frames per second
+    let fast_steps: u32 = (0..250).map(|_| fast.advance(Duration::from_millis(4))).sum();
+    let slow_steps: u32 = (0..25).map(|_| slow.advance(Duration::from_millis(40))).sum();
+
+    assert_eq!(fast_steps, 100);
+    assert_eq!(slow_steps, 100);
+}
+
+#[test]
+fn long_frames_are_clamped_to_max_steps() {
+    let mut timestep = FixedTimestep::new(STEP, 4);
+
+    assert_eq!(timestep.advance(Duration::from_millis(1005)), 4);
+    // The excess is dropped, only the fraction of a step is kept
+    assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+    assert_eq!(timestep.advance(Duration::from_millis(5)), 1);
+}
+
+#[test]
+fn reset_forgets_accumulated_time() {
+    let mut timestep = FixedTimestep::new(STEP, 8);
+    timestep.advance(Duration::from_millis(7));
+    timestep.reset();
+
+    assert_eq!(timestep.alpha(), 0.0);
+    assert_eq!(timestep.advance(Duration::from_millis(7)), 0);
+}
@@ -0,0 +1,114 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use reverie::vulkan::game_object::{Transform2DComponent, Transform3DComponent};
+
+fn assert_vec_eq(actual: uv::Vec2, expected: uv::Vec2) {
+    assert!(
+        (actual - expected).mag() < 1e-5,
+        "expected {:?}, got {:?}", expected, actual
+    );
+}
+
+#[test]
+fn default_transform_is_identity() {
+    let transform = Transform2DComponent::default();
+    assert_eq!(transform.mat2(), uv::Mat2::identity());
+}
+
+#[test]
+fn mat2_scales_before_rotating() {
+    let transform = Transform2DComponent {
+        rotation: FRAC_PI_2,
+        scale: uv::Vec2::new(2.0, 0.5),
+        ..Default::default()
+    };
+
+    assert_vec_eq(transform.mat2() * uv::Vec2::new(1.0, 0.0), uv::Vec2::new(0.0, 2.0));
+    assert_vec_eq(transform.mat2() * uv::Vec2::new(0.0, 1.0), uv::Vec2::new(-0.5, 0.0));
+}
+
+#[test]
+fn look_at_points_x_axis_at_target() {
+    let mut transform = Transform2DComponent {
+        translation: uv::Vec2::new(1.0, 1.0),
+        ..Default::default()
+    };
+
+    transform.look_at(uv::Vec2::new(1.0, 3.0));
+    assert!((transform.rotation - FRAC_PI_2).abs() < 1e-5);
+
+    // Looking at its own position leaves the rotation untouched
+    transform.look_at(uv::Vec2::new(1.0, 1.0));
+    assert!((transform.rotation - FRAC_PI_2).abs() < 1e-5);
+}
+
+#[test]
+fn rotate_around_orbits_and_turns() {
+    let mut transform = Transform2DComponent {
+        translation: uv::Vec2::new(2.0, 0.0),
+        ..Default::default()
+    };
+
+    transform.rotate_around(uv::Vec2::new(1.0, 0.0), PI);
+    assert_vec_eq(transform.translation, uv::Vec2::new(0.0, 0.0));
+    assert!((transform.rotation - PI).abs() < 1e-5);
+}
+
+#[test]
+fn mat4_applies_scale_rotation_then_translation() {
+    let transform = Transform3DComponent {
+        translation: uv::Vec3::new(1.0, 2.0, 3.0),
+        rotation: uv::Rotor3::from_rotation_xy(FRAC_PI_2),
+        scale: uv::Vec3::new(2.0, 1.0, 1.0),
+    };
+
+    let point = transform.mat4() * uv::Vec4::new(1.0, 0.0, 0.0, 1.0);
+    let expected = uv::Vec3::new(1.0, 2.0, 3.0) + {
+        let mut scaled = uv::Vec3::new(2.0, 0.0, 0.0);
+        transform.rotation.rotate_vec(&mut scaled);
+        scaled
+    };
+    assert!((point.xyz() - expected).mag() < 1e-5, "expected {:?}, got {:?}", expected, point);
+}
+
+#[test]
+fn lerp_2d_blends_every_field_and_keeps_spinning() {
+    let from = Transform2DComponent::default();
+    let to = Transform2DComponent {
+        translation: uv::Vec2::new(2.0, -2.0),
+        rotation: 3.0 * PI,
+        scale: uv::Vec2::new(3.0, 1.0),
+        depth: 0.5,
+    };
+
+    let half = from.lerp(&to, 0.5);
+    assert_vec_eq(half.translation, uv::Vec2::new(1.0, -1.0));
+    assert!((half.rotation - 1.5 * PI).abs() < 1e-5);
+    assert_vec_eq(half.scale, uv::Vec2::new(2.0, 1.0));
+    assert!((half.depth - 0.25).abs() < 1e-6);
+
+    assert_eq!(from.lerp(&to, 0.0), from);
+    assert_eq!(from.lerp(&to, 1.0), to);
+}
+
+#[test]
+fn lerp_3d_turns_along_the_shorter_arc() {
+    let from = Transform3DComponent::default();
+    let to = Transform3DComponent {
+        rotation: uv::Rotor3::from_rotation_xy(FRAC_PI_2),
+        ..Default::default()
+    };
+
+    let half = from.lerp(&to, 0.5);
+    let mut x = uv::Vec3::unit_x();
+    half.rotation.rotate_vec(&mut x);
+    let mut expected = uv::Vec3::unit_x();
+    uv::Rotor3::from_rotation_xy(FRAC_PI_2 / 2.0).rotate_vec(&mut expected);
+    assert!((x - expected).mag() < 1e-5, "expected {:?}, got {:?}", expected, x);
+
+    // The negated rotor is the same rotation, interpolating towards it must not take the long way
+    let negated = Transform3DComponent { rotation: to.rotation * -1.0, ..to };
+    let mut x = uv::Vec3::unit_x();
+    from.lerp(&negated, 0.5).rotation.rotate_vec(&mut x);
+    assert!((x - expected).mag() < 1e-5, "expected {:?}, got {:?}", expected, x);
+}